@@ -1,48 +1,141 @@
-use std::{fs, path::Path};
+use std::{env, fs, path::Path};
+
+use tera::Tera;
+
+// build.rs 只用到 rendered_variables
+#[allow(dead_code)]
+#[path = "src/templates/variables.rs"]
+mod variables;
 
 fn main() {
-    let root = Path::new("templates");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    let root = Path::new(&manifest_dir).join("templates");
 
-    let template_files = glob::glob("templates/**/*")
+    let mut template_files: Vec<_> = glob::glob(&format!("{}/**/*", root.display()))
         .expect("Failed to read glob pattern")
         .filter_map(Result::ok)
-        .filter(|entry| entry.is_file());
+        .filter(|entry| entry.is_file())
+        .collect();
+    template_files.sort();
 
-    let mut templates = String::new();
-    let mut map_entries = Vec::new();
+    let mut sources = Vec::new();
+    for file in &template_files {
+        let content = fs::read_to_string(file).expect("Failed to read template file");
+        // 模板相对路径，保留子目录，统一用 `/` 分隔
+        let relative_path = file
+            .strip_prefix(&root)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .replace('\\', "/");
+        sources.push((relative_path, content));
+    }
 
-    for file in template_files {
-        let content = fs::read_to_string(&file).expect("Failed to read template file");
+    // 编译期用 Tera 解析所有模板，语法错误直接让构建失败
+    let mut tera = Tera::default();
+    if let Err(err) = tera.add_raw_templates(sources.iter().map(|(n, c)| (n.as_str(), c.as_str())))
+    {
+        eprintln!("{}", describe_template_error(&err));
+        std::process::exit(1);
+    }
 
-        // 模板相对路径，保留子目录
-        let relative_path = file.strip_prefix(root).unwrap().to_str().unwrap();
+    let mut templates = String::new();
+    let mut map_entries = Vec::new();
+    let mut partial_entries = Vec::new();
+    let mut variable_entries = Vec::new();
 
+    for ((relative_path, _), file) in sources.iter().zip(&template_files) {
         // 生成 Rust 常量名，替换非字母数字为下划线，大写
         let const_name = relative_path
             .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
             .to_uppercase();
 
-        // 写入 Rust 常量
+        // 用 include_str! 引用模板文件，内容里有 `"#` 也不会破坏生成的代码
         templates.push_str(&format!(
-            "pub const {}: &str = r#\"{}\"#;\n",
-            const_name, content
+            "pub const {}: &str = include_str!({:?});\n",
+            const_name,
+            file.display().to_string()
         ));
 
+        // 片段只供其他模板 include/extends/import，不单独渲染，也就不需要变量表
+        // 规则和 templates::is_partial 一致
+        let entry = format!("    ({:?}, {}),", relative_path, const_name);
+        if relative_path.starts_with("_partials/") || relative_path.contains("/_partials/") {
+            partial_entries.push(entry);
+            continue;
+        }
         // 写入映射表，保持相对路径
-        map_entries.push(format!("    (\"{}\", {}),", relative_path, const_name));
+        map_entries.push(entry);
+
+        let variables: Vec<_> = variables::rendered_variables(&tera, relative_path)
+            .into_iter()
+            .collect();
+        variable_entries.push(format!("    ({:?}, &{:?}),", relative_path, variables));
     }
 
     // 生成映射静态变量
-    templates.push_str("\npub static TEMPLATE_MAP: &[(&str, &str)] = &[\n");
+    templates
+        .push_str("\n/// Every embedded template, keyed by its path relative to `templates/`.\n");
+    templates.push_str("pub static TEMPLATE_MAP: &[(&str, &str)] = &[\n");
     for entry in map_entries {
         templates.push_str(&entry);
         templates.push('\n');
     }
     templates.push_str("];\n");
 
-    // 输出到 src/templates.rs
-    let out_path = Path::new("src/templates.rs");
+    templates
+        .push_str("\n/// Embedded templates under `_partials/`, only used by other templates.\n");
+    templates.push_str("pub static PARTIAL_MAP: &[(&str, &str)] = &[\n");
+    for entry in partial_entries {
+        templates.push_str(&entry);
+        templates.push('\n');
+    }
+    templates.push_str("];\n");
+
+    templates.push_str("\n/// Context variables each embedded template needs, sorted by name.\n");
+    templates.push_str("pub static TEMPLATE_VARIABLES: &[(&str, &[&str])] = &[\n");
+    for entry in variable_entries {
+        templates.push_str(&entry);
+        templates.push('\n');
+    }
+    templates.push_str("];\n");
+
+    // 输出到 OUT_DIR，由 src/templates/mod.rs include!
+    let out_path = Path::new(&env::var("OUT_DIR").expect("OUT_DIR not set")).join("templates.rs");
     fs::write(out_path, templates).expect("Failed to write templates.rs");
 
     println!("cargo:rerun-if-changed=templates/");
+    println!("cargo:rerun-if-changed=src/templates/variables.rs");
+}
+
+/// Turns a Tera parse failure into `templates/<file>:<line>:<col>` plus the
+/// parser's own explanation.
+fn describe_template_error(err: &tera::Error) -> String {
+    let mut name = None;
+    let mut location = None;
+    let mut details = Vec::new();
+
+    let mut current: Option<&dyn std::error::Error> = Some(err);
+    while let Some(e) = current {
+        let message = e.to_string();
+        if name.is_none() {
+            name = message
+                .split('\'')
+                .nth(1)
+                .filter(|_| message.starts_with("Failed to parse"))
+                .map(str::to_owned);
+        }
+        if location.is_none() {
+            location = message
+                .lines()
+                .find_map(|line| line.trim_start().strip_prefix("--> "))
+                .map(|pos| pos.trim().to_owned());
+        }
+        details.push(message);
+        current = e.source();
+    }
+
+    let file = name.map_or_else(|| "templates".to_owned(), |n| format!("templates/{n}"));
+    let position = location.map_or_else(String::new, |pos| format!(":{pos}"));
+    format!("invalid template {file}{position}\n{}", details.join("\n"))
 }
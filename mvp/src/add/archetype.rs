@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::templates::TEMPLATE_MAP;
+
+/// Directory under `templates/` holding one sub-directory per archetype.
+const ARCHETYPE_DIR: &str = "archetypes/";
+
+/// Manifests are stored as `Cargo.toml.tera` so cargo never mistakes a
+/// template directory for a package.
+const TEMPLATE_SUFFIX: &str = ".tera";
+
+/// Starting points for `mvp init --archetype`, each a directory of templates
+/// under `templates/archetypes/` mirroring the generated project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Archetype {
+    /// clap binary with subcommands
+    Cli,
+    /// Library with an error module and integration tests
+    Lib,
+    /// Async HTTP server on tokio and axum
+    Service,
+    /// Virtual manifest with a library and a binary member
+    Workspace,
+    /// Derive macro crate built on syn and quote
+    ProcMacro,
+}
+
+impl Archetype {
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Archetype::Cli => "cli",
+            Archetype::Lib => "lib",
+            Archetype::Service => "service",
+            Archetype::Workspace => "workspace",
+            Archetype::ProcMacro => "proc-macro",
+        }
+    }
+
+    /// Every file of this archetype as (path in the project, template name).
+    pub fn files(self) -> Vec<(PathBuf, &'static str)> {
+        let prefix = format!("{ARCHETYPE_DIR}{}/", self.dir_name());
+        TEMPLATE_MAP
+            .iter()
+            .filter_map(|(name, _)| {
+                let dest = name.strip_prefix(&prefix)?;
+                let dest = dest.strip_suffix(TEMPLATE_SUFFIX).unwrap_or(dest);
+                Some((PathBuf::from(dest), *name))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::ValueEnum;
+
+    use super::*;
+
+    #[test]
+    fn every_archetype_has_a_manifest_and_sources() {
+        for archetype in Archetype::value_variants() {
+            let files = archetype.files();
+            assert!(
+                files
+                    .iter()
+                    .any(|(dest, _)| dest == Path::new("Cargo.toml")),
+                "{archetype:?} has no Cargo.toml"
+            );
+            assert!(
+                files
+                    .iter()
+                    .any(|(dest, _)| dest.extension().is_some_and(|e| e == "rs")),
+                "{archetype:?} has no sources"
+            );
+        }
+        assert!(Archetype::Workspace.files().contains(&(
+            PathBuf::from("crates/core/Cargo.toml"),
+            "archetypes/workspace/crates/core/Cargo.toml.tera"
+        )));
+    }
+}
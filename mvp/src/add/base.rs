@@ -1,8 +1,25 @@
 use tera::{Context, Tera};
 
-use crate::error::MvpError;
+use crate::{error::MvpError, plan::Plan};
 
 pub trait AddStrategy: Sync + Send {
-    fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError>;
+    /// Records the files this strategy produces in `plan`; nothing is written
+    /// until the plan is applied.
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError>;
     fn name(&self) -> &str;
 }
+
+/// Parses the extra arguments of `mvp add <name> <args>..` for a built-in
+/// strategy.
+pub fn parse_args<T: clap::Parser>(strategy: &str, context: &Context) -> Result<T, MvpError> {
+    let args: Vec<String> = context
+        .get("args")
+        .and_then(|v| tera::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    T::try_parse_from(std::iter::once(strategy.to_owned()).chain(args)).map_err(|e| {
+        MvpError::InvalidArgs {
+            strategy: strategy.to_owned(),
+            message: e.to_string().trim_end().to_owned(),
+        }
+    })
+}
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use super::{
+    hooks::{self, FileSource, Generation},
+    prelude::*,
+};
+use crate::{
+    config::{HooksConfig, StrategyConfig},
+    templates,
+};
+
+/// A strategy declared under `[strategies.<name>]` in `config.toml`,
+/// rendering existing templates to the listed destinations, with optional
+/// [hooks] around the rendering.
+pub struct ConfigStrategy {
+    name: String,
+    files: Vec<(PathBuf, String)>,
+    hooks: HooksConfig,
+}
+
+impl ConfigStrategy {
+    pub fn new(name: &str, config: &StrategyConfig) -> Self {
+        Self {
+            name: name.to_owned(),
+            files: config
+                .files
+                .iter()
+                .map(|(dest, template)| (dest.clone(), template.clone()))
+                .collect(),
+            hooks: config.hooks.clone(),
+        }
+    }
+
+    fn run_hook(
+        &self,
+        script: Option<&PathBuf>,
+        context: &mut Context,
+        generation: &mut Generation,
+    ) -> Result<(), MvpError> {
+        match script {
+            Some(script) => hooks::run(script, &self.hooks.commands, context, generation),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AddStrategy for ConfigStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let mut generation = Generation {
+            files: self
+                .files
+                .iter()
+                .map(|(dest, template)| (dest.clone(), FileSource::Template(template.clone())))
+                .collect(),
+            commands: Vec::new(),
+        };
+        // pre hook 的命令排在写文件之前，post hook 的排在之后
+        self.run_hook(self.hooks.pre.as_ref(), context, &mut generation)?;
+        render(tera, context, &mut generation)?;
+        for (program, args) in generation.commands.drain(..) {
+            plan.run(&program, args);
+        }
+        // post hook 看到的是渲染好的内容，它新加的模板再渲染一次
+        self.run_hook(self.hooks.post.as_ref(), context, &mut generation)?;
+        render(tera, context, &mut generation)?;
+        for (dest, source) in generation.files {
+            if let FileSource::Text(content) = source {
+                plan.write(dest, content);
+            }
+        }
+        for (program, args) in generation.commands {
+            plan.run(&program, args);
+        }
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Renders every file still given as a template.
+fn render(tera: &Tera, context: &Context, generation: &mut Generation) -> Result<(), MvpError> {
+    for (_, source) in &mut generation.files {
+        let FileSource::Template(template) = source else {
+            continue;
+        };
+        if templates::is_partial(template) {
+            return Err(MvpError::Custom(format!(
+                "`{template}` is a partial and can only be used from other templates"
+            )));
+        }
+        // 有类型化上下文的模板照常校验，其余直接用共享上下文
+        let typed = contexts::context_for(template, context).transpose()?;
+        let content = templates::render(tera, template, typed.as_ref().unwrap_or(context))
+            .map_err(|e| {
+                MvpError::template_render(template, e, |name| templates::source_in(tera, name))
+            })?;
+        *source = FileSource::Text(content);
+    }
+    Ok(())
+}
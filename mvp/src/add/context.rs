@@ -1,6 +1,32 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::{collections::HashMap, ops::Deref, sync::LazyLock};
 
-use super::{base::AddStrategy, impls::*};
+use super::{
+    base::AddStrategy,
+    config_strategy::ConfigStrategy,
+    impls::*,
+    plugin::{PluginStrategy, plugin_dirs},
+};
+use crate::config::Config;
+
+/// A strategy resolved by name: built in, declared in `config.toml`, or an
+/// external plugin.
+pub enum ResolvedStrategy<'a> {
+    Builtin(&'a dyn AddStrategy),
+    Config(ConfigStrategy),
+    Plugin(PluginStrategy),
+}
+
+impl<'a> Deref for ResolvedStrategy<'a> {
+    type Target = dyn AddStrategy + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ResolvedStrategy::Builtin(strategy) => *strategy,
+            ResolvedStrategy::Config(strategy) => strategy,
+            ResolvedStrategy::Plugin(plugin) => plugin,
+        }
+    }
+}
 
 pub struct AddStrategyFactory {
     handlers: HashMap<String, Box<dyn AddStrategy>>,
@@ -26,6 +52,56 @@ impl AddStrategyFactory {
         self.handlers.get(k).map(|b| b.as_ref())
     }
 
+    /// Built-in strategy `k`, then one declared in `config`, then an
+    /// `mvp-strategy-<k>` plugin.
+    pub fn resolve(&self, k: &str, config: &Config) -> Option<ResolvedStrategy<'_>> {
+        self.get(k)
+            .map(ResolvedStrategy::Builtin)
+            .or_else(|| {
+                config
+                    .strategies
+                    .get(k)
+                    .map(|c| ResolvedStrategy::Config(ConfigStrategy::new(k, c)))
+            })
+            .or_else(|| PluginStrategy::discover(k).map(ResolvedStrategy::Plugin))
+    }
+
+    /// Names of the built-in strategies, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.handlers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Every strategy `mvp add` can resolve, with a short description:
+    /// built-in ones, those declared in `config`, then installed plugins.
+    /// Used for shell completion.
+    pub fn available(&self, config: &Config) -> Vec<(String, String)> {
+        let mut available: Vec<(String, String)> = self
+            .names()
+            .into_iter()
+            .map(|name| (name.to_owned(), "built-in".to_owned()))
+            .collect();
+        for (name, strategy) in &config.strategies {
+            if self.get(name).is_none() {
+                let description = strategy
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "from config.toml".to_owned());
+                available.push((name.clone(), description));
+            }
+        }
+        for plugin in PluginStrategy::installed(plugin_dirs()) {
+            if !available.iter().any(|(name, _)| name == plugin.name()) {
+                available.push((
+                    plugin.name().to_owned(),
+                    plugin.path().display().to_string(),
+                ));
+            }
+        }
+        available
+    }
+
     pub fn get_add_strategy_factory() -> &'static AddStrategyFactory {
         static FACTORY: LazyLock<AddStrategyFactory> = LazyLock::new(|| {
             let mut factory = AddStrategyFactory::new();
@@ -35,6 +111,18 @@ impl AddStrategyFactory {
             factory.register(Box::new(VscodeStrategy));
             factory.register(Box::new(FmtStrategy));
             factory.register(Box::new(GitIgnoreStrategy));
+            factory.register(Box::new(ToolchainStrategy));
+            factory.register(Box::new(ClippyStrategy));
+            factory.register(Box::new(DenyStrategy));
+            factory.register(Box::new(CargoStrategy));
+            factory.register(Box::new(ModuleStrategy));
+            factory.register(Box::new(ErrorStrategy));
+            factory.register(Box::new(BenchStrategy));
+            factory.register(Box::new(TestsStrategy));
+            factory.register(Box::new(ChangelogStrategy));
+            factory.register(Box::new(HeadersStrategy));
+            factory.register(Box::new(DockerStrategy));
+            factory.register(Box::new(DevcontainerStrategy));
             factory
         });
         &FACTORY
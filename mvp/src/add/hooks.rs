@@ -0,0 +1,313 @@
+//! Pre- and post-generation hooks for configured strategies, scripted in
+//! [Rhai](https://rhai.rs).
+//!
+//! A hook sees the run context as the `ctx` map and may change it, and can
+//! shape the generated files and queue commands:
+//!
+//! ```rhai
+//! ctx.crate_name = ctx.name;
+//! ctx.crate_name.replace("-", "_");
+//! if !ctx.with_ci { skip(".github/workflows/ci.yml"); }
+//! add_file("NOTICE", "LICENSE.md");   // rendered like the configured files
+//! write("VERSION", "0.1.0\n");        // literal contents
+//! print(files());                     // destinations generated so far
+//! run("cargo", ["fmt"]);              // only whitelisted programs
+//! ```
+//!
+//! Scripts get no filesystem, network or process access beyond this API —
+//! `import` is disabled, so they cannot load other scripts either — and
+//! `run` only accepts the programs listed in the hook's `commands`; commands
+//! are recorded in the plan like any other operation, so
+//! `--dry-run` shows them without running anything.
+
+use std::{
+    fs, mem,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope};
+use tera::Context;
+
+use crate::{error::MvpError, report};
+
+/// Where a generated file's contents come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSource {
+    /// A template, rendered with the (possibly hook-modified) context.
+    Template(String),
+    /// Contents written as they are.
+    Text(String),
+}
+
+/// What a configured strategy is about to generate, as hooks see it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Generation {
+    pub files: Vec<(PathBuf, FileSource)>,
+    /// `(program, args)` pairs queued with `run`.
+    pub commands: Vec<(String, Vec<String>)>,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Runs the hook at `script` against `context` and `generation`. Only
+/// programs listed in `allowed` can be queued with `run`.
+pub fn run(
+    script: &Path,
+    allowed: &[String],
+    context: &mut Context,
+    generation: &mut Generation,
+) -> Result<(), MvpError> {
+    let failed = |message: String| MvpError::HookFailed {
+        path: script.to_path_buf(),
+        message,
+    };
+    let source = fs::read_to_string(script).map_err(|e| failed(e.to_string()))?;
+    report::debug(format!("Running hook {}", script.display()));
+
+    let state = Arc::new(Mutex::new(mem::take(generation)));
+    let engine = engine(state.clone(), allowed.to_vec());
+    let ctx =
+        rhai::serde::to_dynamic(context.clone().into_json()).map_err(|e| failed(e.to_string()))?;
+    let mut scope = Scope::new();
+    scope.push_dynamic("ctx", ctx);
+    let result = engine.run_with_scope(&mut scope, &source);
+    drop(engine);
+    *generation = Arc::try_unwrap(state)
+        .map(|m| m.into_inner().unwrap_or_else(|e| e.into_inner()))
+        .unwrap_or_default();
+    result.map_err(|e| failed(e.to_string()))?;
+
+    let ctx = scope.get_value::<Dynamic>("ctx").unwrap_or_default();
+    let value: serde_json::Value =
+        rhai::serde::from_dynamic(&ctx).map_err(|e| failed(format!("invalid `ctx`: {e}")))?;
+    *context = Context::from_value(value).map_err(|e| failed(format!("invalid `ctx`: {e}")))?;
+    Ok(())
+}
+
+/// A Rhai engine with resource limits and the hook API bound to `state`.
+fn engine(state: Arc<Mutex<Generation>>, allowed: Vec<String>) -> Engine {
+    let mut engine = Engine::new();
+    // 默认的模块解析器会按 `import` 的路径读取任意 .rhai 文件
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000)
+        .disable_symbol("eval")
+        .on_print(|text| report::progress(text))
+        .on_debug(|text, _, pos| report::debug(format!("hook {pos}: {text}")));
+
+    let files = state.clone();
+    engine.register_fn("files", move || -> Array {
+        lock(&files)
+            .files
+            .iter()
+            .map(|(dest, _)| Dynamic::from(dest.display().to_string()))
+            .collect()
+    });
+    let skip = state.clone();
+    engine.register_fn("skip", move |dest: &str| -> ScriptResult<()> {
+        let dest = destination(dest)?;
+        lock(&skip).files.retain(|(path, _)| *path != dest);
+        Ok(())
+    });
+    let add_file = state.clone();
+    engine.register_fn(
+        "add_file",
+        move |dest: &str, template: &str| -> ScriptResult<()> {
+            let source = FileSource::Template(template.to_owned());
+            set_file(&mut lock(&add_file), destination(dest)?, source);
+            Ok(())
+        },
+    );
+    let write = state.clone();
+    engine.register_fn(
+        "write",
+        move |dest: &str, contents: &str| -> ScriptResult<()> {
+            let source = FileSource::Text(contents.to_owned());
+            set_file(&mut lock(&write), destination(dest)?, source);
+            Ok(())
+        },
+    );
+    engine.register_fn(
+        "run",
+        move |program: &str, args: Array| -> ScriptResult<()> {
+            if !allowed.iter().any(|a| a == program) {
+                let allowed = if allowed.is_empty() {
+                    "none; list programs in the hook's `commands`".to_owned()
+                } else {
+                    allowed.join(", ")
+                };
+                return Err(
+                    format!("`{program}` is not an allowed command (allowed: {allowed})").into(),
+                );
+            }
+            let args = args
+                .into_iter()
+                .map(|arg| {
+                    arg.into_string()
+                        .map_err(|_| "`run` arguments must be strings")
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            lock(&state).commands.push((program.to_owned(), args));
+            Ok(())
+        },
+    );
+    engine
+}
+
+fn lock(state: &Mutex<Generation>) -> std::sync::MutexGuard<'_, Generation> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Replaces the file at `dest`, or adds it at the end.
+fn set_file(generation: &mut Generation, dest: PathBuf, source: FileSource) {
+    match generation.files.iter_mut().find(|(path, _)| *path == dest) {
+        Some((_, existing)) => *existing = source,
+        None => generation.files.push((dest, source)),
+    }
+}
+
+/// Hooks may only name files inside the target directory.
+fn destination(dest: &str) -> ScriptResult<PathBuf> {
+    let path = PathBuf::from(dest);
+    let inside = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if dest.is_empty() || !inside {
+        return Err(format!("`{dest}` is not a relative path inside the target").into());
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    /// Runs `body` as a hook script.
+    fn run_script(
+        body: &str,
+        allowed: &[&str],
+        context: &mut Context,
+        generation: &mut Generation,
+    ) -> Result<(), MvpError> {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("hook.rhai");
+        fs::write(&script, body).unwrap();
+        let allowed: Vec<String> = allowed.iter().map(|&a| a.to_owned()).collect();
+        run(&script, &allowed, context, generation)
+    }
+
+    fn template(name: &str) -> FileSource {
+        FileSource::Template(name.to_owned())
+    }
+
+    #[test]
+    fn hooks_edit_the_context() {
+        let mut context = Context::new();
+        context.insert("name", "my-app");
+        let result = run_script(
+            r#"ctx.crate_name = ctx.name; ctx.crate_name.replace("-", "_");"#,
+            &[],
+            &mut context,
+            &mut Generation::default(),
+        );
+        result.unwrap();
+        assert_eq!(context.get("crate_name").unwrap(), "my_app");
+        assert_eq!(context.get("name").unwrap(), "my-app");
+    }
+
+    #[test]
+    fn hooks_skip_and_add_files() {
+        let mut context = Context::new();
+        context.insert("with_ci", &false);
+        let mut generation = Generation {
+            files: vec![
+                ("README.md".into(), template("README.md")),
+                ("ci.yml".into(), template("ci.yml")),
+            ],
+            commands: Vec::new(),
+        };
+        let result = run_script(
+            r#"
+            if !ctx.with_ci { skip("ci.yml"); }
+            add_file("NOTICE", "LICENSE-MIT");
+            write("VERSION", `${files().len()}`);
+            "#,
+            &[],
+            &mut context,
+            &mut generation,
+        );
+        result.unwrap();
+        assert_eq!(
+            generation.files,
+            [
+                ("README.md".into(), template("README.md")),
+                ("NOTICE".into(), template("LICENSE-MIT")),
+                ("VERSION".into(), FileSource::Text("2".into())),
+            ]
+        );
+        assert!(generation.commands.is_empty());
+    }
+
+    #[test]
+    fn hooks_queue_only_allowed_commands() {
+        let mut generation = Generation::default();
+        let result = run_script(
+            r#"run("cargo", ["fmt"]);"#,
+            &["cargo"],
+            &mut Context::new(),
+            &mut generation,
+        );
+        result.unwrap();
+        assert_eq!(
+            generation.commands,
+            [("cargo".to_owned(), vec!["fmt".to_owned()])]
+        );
+
+        // 默认的白名单是空的
+        let result = run_script(
+            r#"run("cargo", ["fmt"]);"#,
+            &[],
+            &mut Context::new(),
+            &mut Generation::default(),
+        );
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("allowed: none"), "{err}");
+    }
+
+    #[test]
+    fn hooks_cannot_leave_the_sandbox() {
+        let outside = tempdir().unwrap();
+        fs::write(
+            outside.path().join("module.rhai"),
+            "export const SECRET = 1;",
+        )
+        .unwrap();
+        let import = format!("import {:?} as m;", outside.path().join("module").display());
+        for (body, message) in [
+            (r#"run("rm", ["-rf", "/"]);"#, "not an allowed command"),
+            (r#"write("../escape", "x");"#, "not a relative path"),
+            ("loop {}", "Too many operations"),
+            (import.as_str(), "Module not found"),
+        ] {
+            let result = run_script(
+                body,
+                &["cargo"],
+                &mut Context::new(),
+                &mut Generation::default(),
+            );
+            let err = result.unwrap_err();
+            assert!(
+                matches!(&err, MvpError::HookFailed { message: m, .. } if m.contains(message)),
+                "{body}: {err}"
+            );
+        }
+    }
+}
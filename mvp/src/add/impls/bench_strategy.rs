@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use super::super::prelude::*;
+use crate::add::{manifest::Manifest, rust_source::SourceFiles};
+
+pub struct BenchStrategy;
+
+/// `mvp add bench <name> [--inputs 10,100,1000]`
+#[derive(clap::Parser)]
+struct BenchArgs {
+    /// Name of the bench target and of `benches/<name>.rs`
+    name: String,
+    /// Inputs the benchmark group is parameterized over
+    #[arg(long, value_delimiter = ',', default_values_t = [100, 1_000, 10_000])]
+    inputs: Vec<u64>,
+}
+
+/// Version added as a dev-dependency, as in `high-performance-lab/false-sharing`.
+const CRITERION_VERSION: &str = "0.7";
+
+// Add a criterion benchmark: benches/<name>.rs, [[bench]] and the dev-dependency
+impl AddStrategy for BenchStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let args: BenchArgs = parse_args(self.name(), context)?;
+        let fn_name = args.name.replace('-', "_");
+        if syn::parse_str::<syn::Ident>(&fn_name).is_err() {
+            return Err(MvpError::InvalidArgs {
+                strategy: self.name().to_owned(),
+                message: format!("`{}` is not a valid bench name", args.name),
+            });
+        }
+
+        let mut files = SourceFiles::new(plan.root());
+        let bench_rs = PathBuf::from(format!("benches/{}.rs", args.name));
+        if files.exists(&bench_rs)? {
+            return Err(MvpError::FileExists {
+                path: plan.root().join(bench_rs),
+            });
+        }
+        let cargo_toml = Path::new("Cargo.toml");
+        let text = files.read(cargo_toml)?.ok_or_else(|| {
+            MvpError::Custom(format!("No Cargo.toml in {}", plan.root().display()))
+        })?;
+        let mut manifest = Manifest::parse(cargo_toml, text)?;
+        if !manifest.add_target("bench", &args.name, &[("harness", false.into())])? {
+            return Err(MvpError::InvalidArgs {
+                strategy: self.name().to_owned(),
+                message: format!("Cargo.toml already declares a bench named `{}`", args.name),
+            });
+        }
+        manifest.add_dependency("dev-dependencies", "criterion", CRITERION_VERSION);
+        files.set(cargo_toml, manifest.to_string());
+
+        let content = contexts::BenchContext {
+            name: args.name,
+            fn_name,
+            inputs: args.inputs,
+        }
+        .render(tera, "bench.rs")?;
+        files.set(&bench_rs, content);
+        files.finish(plan);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "bench"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn existing_bench_is_refused() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[[bench]]\nname = \"sum\"\nharness = false\n",
+        )
+        .unwrap();
+        let mut context = Context::new();
+        context.insert("args", &["sum"]);
+        let mut plan = Plan::new(dir.path());
+        let err = BenchStrategy
+            .handle(&crate::templates::TEMPLATES, &mut context, &mut plan)
+            .unwrap_err();
+        assert!(matches!(err, MvpError::InvalidArgs { .. }), "{err}");
+        assert!(plan.is_empty());
+
+        context.insert("args", &["other", "--inputs", "1,2"]);
+        BenchStrategy
+            .handle(&crate::templates::TEMPLATES, &mut context, &mut plan)
+            .unwrap();
+        let paths: Vec<_> = plan.ops().iter().map(|op| op.path()).collect();
+        assert_eq!(
+            paths,
+            [Path::new("Cargo.toml"), Path::new("benches/other.rs")]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use super::super::prelude::*;
+
+pub struct CargoStrategy;
+
+// Add .cargo/config.toml with our aliases
+impl AddStrategy for CargoStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content =
+            contexts::CargoContext::from_context(context)?.render(tera, "cargo/config.toml")?;
+        plan.write(".cargo/config.toml", content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "cargo"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Generator, config::Config, plan::FileOp};
+
+    #[test]
+    fn aliases_follow_config_and_stay_valid_toml() {
+        let config: Config =
+            toml::from_str("[cargo.aliases]\nx = \"run -p xtask --\"\n\"ci check\" = \"clippy\"\n")
+                .unwrap();
+        let plan = Generator::builder()
+            .config(config)
+            .strategies(["cargo"])
+            .plan()
+            .unwrap();
+
+        let [FileOp::Write { path, contents }] = plan.ops() else {
+            panic!("{:?}", plan.ops());
+        };
+        assert_eq!(path.to_str(), Some(".cargo/config.toml"));
+        let toml: toml::Table = toml::from_str(contents).unwrap();
+        assert_eq!(toml["alias"]["x"].as_str(), Some("run -p xtask --"));
+        assert_eq!(toml["alias"]["ci check"].as_str(), Some("clippy"));
+    }
+}
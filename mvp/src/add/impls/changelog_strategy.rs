@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use super::super::prelude::*;
+
+pub struct ChangelogStrategy;
+
+// Add a Keep-a-Changelog CHANGELOG.md
+impl AddStrategy for ChangelogStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let changelog = Path::new("CHANGELOG.md");
+        if plan.root().join(changelog).exists() {
+            return Err(MvpError::FileExists {
+                path: plan.root().join(changelog),
+            });
+        }
+        let content =
+            contexts::ChangelogContext::from_context(context)?.render(tera, "CHANGELOG.md")?;
+        plan.write(changelog, content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "changelog"
+    }
+}
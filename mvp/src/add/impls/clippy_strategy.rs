@@ -0,0 +1,16 @@
+use super::super::prelude::*;
+
+pub struct ClippyStrategy;
+
+// Add clippy.toml lint thresholds
+impl AddStrategy for ClippyStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content =
+            contexts::ClippyContext::from_context(context)?.render(tera, "clippy.toml")?;
+        plan.write("clippy.toml", content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "clippy"
+    }
+}
@@ -22,10 +22,15 @@ impl Default for Composite {
 }
 
 impl Composite {
-    pub fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError> {
+    pub fn handle(
+        &self,
+        tera: &Tera,
+        context: &mut Context,
+        plan: &mut Plan,
+    ) -> Result<(), MvpError> {
         for strat in &self.strategies {
-            println!("Running strategy: {}", strat.name());
-            strat.handle(tera, context)?;
+            report::progress(Message::RunningStrategy(strat.name()));
+            strat.handle(tera, context, plan)?;
         }
         Ok(())
     }
@@ -0,0 +1,15 @@
+use super::super::prelude::*;
+
+pub struct DenyStrategy;
+
+// Add the cargo-deny license and advisory policy
+impl AddStrategy for DenyStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content = contexts::DenyContext::from_context(context)?.render(tera, "deny.toml")?;
+        plan.write("deny.toml", content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "deny"
+    }
+}
@@ -0,0 +1,37 @@
+use super::super::prelude::*;
+use crate::templates::{contexts::DevcontainerContext, format};
+
+pub struct DevcontainerStrategy;
+
+// Add a dev container with rust-analyzer and the settings of `mvp add vscode`
+impl AddStrategy for DevcontainerStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let vscode = contexts::VscodeContext::from_context(context)?;
+        let settings = vscode.render(tera, "vscode/settings.json")?;
+        let settings = format::parse_jsonc(&settings)
+            .ok()
+            .and_then(|settings| match settings {
+                serde_json::Value::Object(settings) => Some(settings),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                MvpError::Custom("vscode/settings.json did not render to a JSON object".into())
+            })?;
+        // 虚拟工作区没有包名，用目录名
+        let name = match vscode.project.package.name.as_str() {
+            "" => std::path::absolute(plan.root())?
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            name => name.to_owned(),
+        };
+
+        let content = DevcontainerContext { name, settings }
+            .render(tera, "devcontainer/devcontainer.json")?;
+        plan.write(".devcontainer/devcontainer.json", content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "devcontainer"
+    }
+}
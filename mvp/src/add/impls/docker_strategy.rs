@@ -0,0 +1,221 @@
+use std::{fs, path::Path};
+
+use super::super::prelude::*;
+use crate::{
+    plan::FileOp,
+    project::Project,
+    templates::contexts::{DockerContext, DockerRuntime},
+};
+
+pub struct DockerStrategy;
+
+/// `mvp add docker [--runtime scratch] [--bin <name>]`
+#[derive(clap::Parser)]
+struct DockerArgs {
+    /// Base of the final image
+    #[arg(long, value_enum, default_value_t)]
+    runtime: DockerRuntime,
+    /// Binary the image runs; defaults to the one named after the package
+    #[arg(long)]
+    bin: Option<String>,
+}
+
+// Add a multi-stage Dockerfile for the package's binaries and a .dockerignore
+impl AddStrategy for DockerStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let args: DockerArgs = parse_args(self.name(), context)?;
+        let project: Project = context
+            .get("project")
+            .and_then(|v| tera::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let package = project.package;
+        if package.name.is_empty() {
+            return Err(MvpError::Custom(format!(
+                "No package in {}",
+                plan.root().display()
+            )));
+        }
+        let entrypoint = match args.bin {
+            Some(bin) if package.bins.contains(&bin) => bin,
+            Some(bin) => {
+                return Err(MvpError::InvalidArgs {
+                    strategy: self.name().to_owned(),
+                    message: format!("`{}` has no binary named `{bin}`", package.name),
+                });
+            }
+            None if package.bins.contains(&package.name) => package.name.clone(),
+            None => package
+                .bins
+                .first()
+                .cloned()
+                .ok_or_else(|| MvpError::InvalidArgs {
+                    strategy: self.name().to_owned(),
+                    message: format!("`{}` has no binary target", package.name),
+                })?,
+        };
+
+        // 工作区成员要以工作区根目录为构建上下文，cargo-chef 才看得到整个工作区
+        let in_workspace = !package.path.is_empty();
+        let context_root = if in_workspace {
+            Path::new(&project.workspace.root).to_path_buf()
+        } else {
+            plan.root().to_path_buf()
+        };
+        let gitignore = match planned_gitignore(plan).filter(|_| !in_workspace) {
+            Some(text) => text,
+            None => match fs::read_to_string(context_root.join(".gitignore")) {
+                Ok(text) => text,
+                Err(_) => contexts::GitIgnoreContext::default().render(tera, ".gitignore")?,
+            },
+        };
+        // 仓库元数据和构建产物无论 .gitignore 怎么写都不进构建上下文
+        let mut ignore = vec![".git".to_owned(), "**/target".to_owned()];
+        for pattern in gitignore.lines().filter_map(dockerignore_pattern) {
+            if !ignore.contains(&pattern) {
+                ignore.push(pattern);
+            }
+        }
+
+        let docker = DockerContext {
+            name: package.name,
+            path: package.path,
+            bins: package.bins,
+            entrypoint,
+            runtime: args.runtime,
+            locked: context_root.join("Cargo.lock").is_file(),
+            ignore,
+        };
+        plan.write("Dockerfile", docker.render(tera, "docker/Dockerfile")?);
+        // BuildKit 优先读取 Dockerfile 旁边的 <Dockerfile>.dockerignore
+        let ignore_file = if in_workspace {
+            "Dockerfile.dockerignore"
+        } else {
+            ".dockerignore"
+        };
+        plan.write(ignore_file, docker.render(tera, "docker/.dockerignore")?);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "docker"
+    }
+}
+
+/// `.gitignore` as an earlier strategy of this run (`mvp add gi docker`)
+/// writes it.
+fn planned_gitignore(plan: &Plan) -> Option<String> {
+    plan.ops().iter().rev().find_map(|op| match op {
+        FileOp::Write { path, contents } if path == Path::new(".gitignore") => {
+            Some(contents.clone())
+        }
+        _ => None,
+    })
+}
+
+/// Translates one `.gitignore` line. Git matches a pattern without a slash
+/// at any depth and Docker only at the context root, so those get a `**/`;
+/// the trailing `/` of directory patterns has no Docker equivalent.
+fn dockerignore_pattern(line: &str) -> Option<String> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negate, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => ("!", pattern),
+        None => ("", line),
+    };
+    let pattern = pattern.strip_suffix('/').unwrap_or(pattern);
+    let pattern = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_owned(),
+        None if pattern.contains('/') => pattern.to_owned(),
+        None => format!("**/{pattern}"),
+    };
+    // 库项目常忽略 Cargo.lock，但 `--locked` 构建需要它
+    if pattern.is_empty() || pattern.ends_with("Cargo.lock") {
+        return None;
+    }
+    Some(format!("{negate}{pattern}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{Generator, config::Config};
+
+    #[test]
+    fn gitignore_patterns_become_context_relative() {
+        let gitignore =
+            "# build\n/target\ndebug/\n*.swp\n\n!keep.swp\ndocs/generated\nCargo.lock\n";
+        let patterns: Vec<_> = gitignore.lines().filter_map(dockerignore_pattern).collect();
+        assert_eq!(
+            patterns,
+            [
+                "target",
+                "**/debug",
+                "**/*.swp",
+                "!**/keep.swp",
+                "docs/generated"
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_members_build_from_the_root() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("crates/cli/src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::write(root.join("Cargo.lock"), "").unwrap();
+        fs::write(root.join(".gitignore"), "/target\n").unwrap();
+        fs::write(
+            root.join("crates/cli/Cargo.toml"),
+            "[package]\nname = \"cli\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("crates/cli/src/main.rs"), "fn main() {}").unwrap();
+
+        let plan = Generator::builder()
+            .target(root.join("crates/cli"))
+            .config(Config::default())
+            .strategies(["docker"])
+            .args(["--runtime", "scratch"])
+            .plan()
+            .unwrap();
+        let [
+            FileOp::Write {
+                contents: dockerfile,
+                ..
+            },
+            FileOp::Write { path, contents },
+        ] = plan.ops()
+        else {
+            panic!("{:?}", plan.ops());
+        };
+        assert_eq!(path, Path::new("Dockerfile.dockerignore"));
+        assert_eq!(
+            contents.lines().skip(2).collect::<Vec<_>>(),
+            [".git", "**/target", "target"]
+        );
+        assert!(dockerfile.contains("docker build -f crates/cli/Dockerfile ."));
+        assert!(dockerfile.contains(
+            "RUN cargo build --release --locked -p cli --target x86_64-unknown-linux-musl --bin cli"
+        ));
+        assert!(dockerfile.contains("FROM scratch AS runtime"));
+
+        let err = Generator::builder()
+            .target(root.join("crates/cli"))
+            .config(Config::default())
+            .strategies(["docker"])
+            .args(["--bin", "nope"])
+            .plan()
+            .unwrap_err();
+        assert!(matches!(err, MvpError::InvalidArgs { .. }), "{err}");
+    }
+}
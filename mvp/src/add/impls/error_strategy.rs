@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use super::super::prelude::*;
+use crate::add::{
+    manifest::Manifest,
+    rust_source::{SourceFiles, crate_root},
+};
+
+pub struct ErrorStrategy;
+
+/// `mvp add error` takes no arguments yet.
+#[derive(clap::Parser)]
+struct ErrorArgs {}
+
+/// Dependencies whose error types get a `#[from]` variant: (crate, variant,
+/// type, message).
+const KNOWN_SOURCES: &[(&str, &str, &str, &str)] = &[
+    ("chrono", "Chrono", "chrono::ParseError", "Chrono Error"),
+    ("csv", "Csv", "csv::Error", "CSV Error"),
+    ("git2", "Git", "git2::Error", "Git Error"),
+    ("reqwest", "Http", "reqwest::Error", "HTTP Error"),
+    ("serde_json", "Json", "serde_json::Error", "JSON Error"),
+    ("serde_yaml", "Yaml", "serde_yaml::Error", "YAML Error"),
+    ("sqlx", "Sqlx", "sqlx::Error", "SQLx Error"),
+    ("tera", "Tera", "tera::Error", "Tera Error"),
+    ("toml", "Toml", "toml::de::Error", "TOML Error"),
+    ("url", "Url", "url::ParseError", "URL Error"),
+];
+
+// Add src/error.rs with a thiserror-based Error enum
+impl AddStrategy for ErrorStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let _: ErrorArgs = parse_args(self.name(), context)?;
+        let mut files = SourceFiles::new(plan.root());
+        let error_rs = Path::new("src/error.rs");
+        if files.exists(error_rs)? {
+            return Err(MvpError::FileExists {
+                path: plan.root().join(error_rs),
+            });
+        }
+        let root = crate_root(plan.root()).ok_or_else(|| {
+            MvpError::Custom(format!(
+                "No src/lib.rs or src/main.rs in {}",
+                plan.root().display()
+            ))
+        })?;
+
+        let cargo_toml = Path::new("Cargo.toml");
+        let text = files.read(cargo_toml)?.ok_or_else(|| {
+            MvpError::Custom(format!("No Cargo.toml in {}", plan.root().display()))
+        })?;
+        let mut manifest = Manifest::parse(cargo_toml, text)?;
+        let dependencies = manifest.dependencies("dependencies");
+
+        // io 总是有，其余按 Cargo.toml 里已有的依赖生成 #[from] 变体
+        let sources = [("", "Io", "std::io::Error", "IO Error")]
+            .iter()
+            .chain(KNOWN_SOURCES)
+            .filter(|(krate, ..)| krate.is_empty() || dependencies.contains(*krate))
+            .map(|&(_, variant, ty, message)| contexts::ErrorSource {
+                variant: variant.to_owned(),
+                ty: ty.to_owned(),
+                message: message.to_owned(),
+            })
+            .collect();
+        let content = contexts::ErrorContext { sources }.render(tera, "error.rs")?;
+        files.set(error_rs, content);
+
+        if manifest.add_dependency("dependencies", "thiserror", "2") {
+            report::progress(Message::AddingDependency {
+                name: "thiserror",
+                manifest: "Cargo.toml",
+            });
+            files.set(cargo_toml, manifest.to_string());
+        }
+
+        // 库里公开 Error/Result，二进制里只声明模块
+        let library = root.ends_with("lib.rs");
+        files.declare_mod(&root, "error", library)?;
+        if library {
+            files.declare_use(&root, "error::{Error, Result}", true)?;
+        }
+        files.finish(plan);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "error"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::plan::FileOp;
+
+    #[test]
+    fn error_module_follows_dependencies() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\nserde_json = \"1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub mod model;\n").unwrap();
+
+        let mut plan = Plan::new(dir.path());
+        ErrorStrategy
+            .handle(&crate::templates::TEMPLATES, &mut Context::new(), &mut plan)
+            .unwrap();
+
+        let contents = |path: &str| {
+            plan.ops()
+                .iter()
+                .find_map(|op| match op {
+                    FileOp::Write { path: p, contents } if p == Path::new(path) => {
+                        Some(contents.as_str())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("{path} not written"))
+        };
+        let error_rs = contents("src/error.rs");
+        assert!(
+            error_rs.contains("Io(#[from] std::io::Error)"),
+            "{error_rs}"
+        );
+        assert!(
+            error_rs.contains("Json(#[from] serde_json::Error)"),
+            "{error_rs}"
+        );
+        assert!(!error_rs.contains("tera"), "{error_rs}");
+        assert!(contents("Cargo.toml").contains("thiserror = \"2\""));
+        assert_eq!(
+            contents("src/lib.rs"),
+            "pub mod error;\npub mod model;\n\npub use error::{Error, Result};\n"
+        );
+    }
+}
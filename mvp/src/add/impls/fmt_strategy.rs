@@ -1,15 +1,12 @@
-use std::fs::write;
-
 use super::super::prelude::*;
 
 pub struct FmtStrategy;
 
 // Add rustfmt.toml
 impl AddStrategy for FmtStrategy {
-    fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError> {
-        let content = tera.render("rustfmt.toml", context)?;
-        write("rustfmt.toml", content.as_bytes())?;
-        println!("Created rustfmt.toml");
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content = contexts::FmtContext::from_context(context)?.render(tera, "rustfmt.toml")?;
+        plan.write("rustfmt.toml", content);
         Ok(())
     }
     fn name(&self) -> &str {
@@ -1,15 +1,13 @@
-use std::fs;
-
 use super::super::prelude::*;
 
 pub struct GitIgnoreStrategy;
 
 // Add .gitignore
 impl AddStrategy for GitIgnoreStrategy {
-    fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError> {
-        let content = tera.render(".gitignore", context)?;
-        fs::write(".gitignore", content.as_bytes())?;
-        println!("Created .gitignore");
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content =
+            contexts::GitIgnoreContext::from_context(context)?.render(tera, ".gitignore")?;
+        plan.write(".gitignore", content);
         Ok(())
     }
     fn name(&self) -> &str {
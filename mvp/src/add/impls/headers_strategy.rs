@@ -0,0 +1,310 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::super::prelude::*;
+use crate::config::HeadersConfig;
+
+pub struct HeadersStrategy;
+
+/// `mvp add headers [--check] [--license <SPDX>] [--holder <NAME>]`
+#[derive(clap::Parser)]
+struct HeadersArgs {
+    /// List files with a missing or stale header instead of fixing them
+    #[arg(long)]
+    check: bool,
+    /// SPDX license expression; defaults to `[headers] license` in config.toml
+    #[arg(long)]
+    license: Option<String>,
+    /// Copyright holder; defaults to `[headers] holder`, then the author
+    #[arg(long)]
+    holder: Option<String>,
+}
+
+/// Directories never searched for source files.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+// Add or refresh SPDX/copyright headers in .rs, .toml and shell files
+impl AddStrategy for HeadersStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let args: HeadersArgs = parse_args(self.name(), context)?;
+        let config: HeadersConfig = context
+            .get("headers")
+            .and_then(|v| tera::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let year = context
+            .get("year")
+            .and_then(|v| v.as_i64())
+            .unwrap_or_else(|| i64::from(chrono::Datelike::year(&chrono::Utc::now())));
+        let holder = args
+            .holder
+            .or_else(|| Some(config.holder.clone()).filter(|h| !h.is_empty()))
+            .or_else(|| {
+                context
+                    .get("author")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_owned)
+            })
+            .unwrap_or_default();
+        let license = args.license.unwrap_or(config.license.clone());
+        let exclude: Vec<glob::Pattern> = config
+            .exclude
+            .iter()
+            .map(|p| glob::Pattern::new(p))
+            .collect::<Result<_, _>>()
+            .map_err(|e| MvpError::ConfigInvalid {
+                message: format!("headers.exclude: {e}"),
+            })?;
+
+        let mut files = Vec::new();
+        source_files(plan.root(), Path::new(""), &mut files)?;
+        files.retain(|path| !exclude.iter().any(|p| p.matches_path(path)));
+
+        let mut outdated = Vec::new();
+        for path in files {
+            let Some(comment) = comment_prefix(&path) else {
+                continue;
+            };
+            let text = fs::read_to_string(plan.root().join(&path))?;
+            let existing = find_header(&text, comment);
+            let first_year = existing.as_ref().and_then(|h| h.first_year).unwrap_or(year);
+            let header = contexts::HeaderContext {
+                license: license.clone(),
+                years: if first_year < year {
+                    format!("{first_year}-{year}")
+                } else {
+                    year.to_string()
+                },
+                holder: holder.clone(),
+            }
+            .render(tera, "header.txt")?;
+            let updated = with_header(&text, comment, existing.as_ref(), &header);
+            if updated == text {
+                continue;
+            }
+            let status = if existing.is_some() {
+                "stale"
+            } else {
+                "missing"
+            };
+            if args.check {
+                // 放进错误信息而不是进度输出，`-q` 下也能看到是哪些文件
+                outdated.push(
+                    Message::HeaderOutdated {
+                        path: &path,
+                        stale: existing.is_some(),
+                    }
+                    .to_string(),
+                );
+            } else {
+                report::debug(format!("{status} header: {}", path.display()));
+                plan.write(path, updated);
+            }
+        }
+        if !outdated.is_empty() {
+            return Err(MvpError::CheckFailed {
+                message: format!(
+                    "{} file(s) have a missing or stale license header:\n  {}",
+                    outdated.len(),
+                    outdated.join("\n  ")
+                ),
+            });
+        }
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "headers"
+    }
+}
+
+/// `.rs`, `.toml` and shell files below `root`, skipping hidden and build
+/// directories.
+fn source_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), MvpError> {
+    let mut entries: Vec<_> = fs::read_dir(root.join(dir))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = dir.join(&*name);
+        if entry.file_type()?.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&&*name) {
+                source_files(root, &path, files)?;
+            }
+        } else if comment_prefix(&path).is_some() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn comment_prefix(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "rs" => Some("//"),
+        "toml" | "sh" | "bash" => Some("#"),
+        _ => None,
+    }
+}
+
+/// An SPDX header found in the leading comment block of a file.
+#[derive(Debug, PartialEq, Eq)]
+struct Header {
+    /// Line range of the `SPDX-License-Identifier` and `Copyright` lines.
+    start: usize,
+    end: usize,
+    first_year: Option<i64>,
+}
+
+/// Number of leading lines that must stay first: a shebang, but not an inner
+/// attribute such as `#![allow(..)]`.
+fn preamble(lines: &[&str]) -> usize {
+    match lines.first() {
+        Some(line) if line.starts_with("#!") && !line.starts_with("#![") => 1,
+        _ => 0,
+    }
+}
+
+fn find_header(text: &str, comment: &str) -> Option<Header> {
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = preamble(&lines);
+    // 只看开头连续的普通注释行，`//!` 文档注释不算
+    let block: Vec<(usize, &str)> = lines
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take_while(|(_, line)| {
+            line.starts_with(comment) && !line.starts_with("//!") && !line.starts_with("#!")
+        })
+        .map(|(i, line)| (i, line[comment.len()..].trim()))
+        .collect();
+    let is_header_line =
+        |line: &str| line.starts_with("SPDX-License-Identifier:") || line.starts_with("Copyright");
+    let start = block.iter().find(|(_, l)| is_header_line(l))?.0;
+    let end = block
+        .iter()
+        .rfind(|(_, l)| is_header_line(l))
+        .map(|(i, _)| i + 1)?;
+    if !block
+        .iter()
+        .any(|(_, l)| l.starts_with("SPDX-License-Identifier:"))
+    {
+        return None;
+    }
+    let first_year = block
+        .iter()
+        .filter(|(_, l)| l.starts_with("Copyright"))
+        .flat_map(|(_, l)| l.split(|c: char| !c.is_ascii_digit()))
+        .filter(|word| word.len() == 4)
+        .filter_map(|word| word.parse().ok())
+        .min();
+    Some(Header {
+        start,
+        end,
+        first_year,
+    })
+}
+
+/// `text` with `header` commented out and placed after any shebang, or in
+/// place of the `existing` header lines.
+fn with_header(text: &str, comment: &str, existing: Option<&Header>, header: &str) -> String {
+    let commented: Vec<String> = header
+        .lines()
+        .map(|line| format!("{comment} {line}").trim_end().to_owned())
+        .collect();
+    let mut lines: Vec<String> = text.lines().map(str::to_owned).collect();
+    match existing {
+        Some(h) => {
+            lines.splice(h.start..h.end, commented);
+        }
+        None => {
+            let at = preamble(&text.lines().collect::<Vec<_>>());
+            let mut block = commented;
+            if lines.get(at).is_some_and(|l| !l.trim().is_empty()) {
+                block.push(String::new());
+            }
+            lines.splice(at..at, block);
+        }
+    }
+    let mut out = lines.join("\n");
+    if text.is_empty() || text.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn headers_are_inserted_refreshed_and_checked() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "#![allow(dead_code)]\n\nfn f() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "// SPDX-License-Identifier: MIT OR Apache-2.0\n// Copyright (c) 2021 Me\n\nfn main() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("run.sh"), "#!/bin/sh\necho hi\n").unwrap();
+        fs::write(root.join("target/skip.rs"), "").unwrap();
+
+        let tera = &crate::templates::TEMPLATES;
+        let mut context = Context::new();
+        context.insert("year", &2024);
+        context.insert("args", &["--holder", "Me"]);
+        let mut plan = Plan::new(root);
+        HeadersStrategy
+            .handle(tera, &mut context, &mut plan)
+            .unwrap();
+        let written: Vec<_> = plan
+            .ops()
+            .iter()
+            .map(|op| match op {
+                crate::plan::FileOp::Write { path, contents } => {
+                    (path.display().to_string(), contents.as_str())
+                }
+                _ => panic!("unexpected {op:?}"),
+            })
+            .collect();
+        assert_eq!(
+            written,
+            [
+                (
+                    "run.sh".to_owned(),
+                    "#!/bin/sh\n# SPDX-License-Identifier: MIT OR Apache-2.0\n# Copyright (c) 2024 Me\n\necho hi\n"
+                ),
+                (
+                    "src/lib.rs".to_owned(),
+                    "// SPDX-License-Identifier: MIT OR Apache-2.0\n// Copyright (c) 2024 Me\n\n#![allow(dead_code)]\n\nfn f() {}\n"
+                ),
+                (
+                    "src/main.rs".to_owned(),
+                    "// SPDX-License-Identifier: MIT OR Apache-2.0\n// Copyright (c) 2021-2024 Me\n\nfn main() {}\n"
+                ),
+            ]
+        );
+
+        context.insert("args", &["--holder", "Me", "--check"]);
+        let mut check = Plan::new(root);
+        let err = HeadersStrategy
+            .handle(tera, &mut context, &mut check)
+            .unwrap_err();
+        let MvpError::CheckFailed { message } = &err else {
+            panic!("{err}");
+        };
+        assert_eq!(
+            message,
+            "3 file(s) have a missing or stale license header:\n  missing: run.sh\n  missing: src/lib.rs\n  stale: src/main.rs"
+        );
+        assert!(check.is_empty());
+    }
+}
@@ -1,11 +1,15 @@
-use std::process::Command;
+use std::path::Path;
 
-use super::{composite::Composite, super::prelude::*};
+use super::{super::prelude::*, composite::Composite};
+use crate::{
+    add::archetype::Archetype,
+    project::{Git, Package, Project},
+};
 
 pub struct InitStrategy;
 
 impl AddStrategy for InitStrategy {
-    fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError> {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
         let values = context
             .get("init_values")
             .and_then(|v| v.as_array())
@@ -14,9 +18,19 @@ impl AddStrategy for InitStrategy {
         let project_name = values
             .first()
             .and_then(|v| v.as_str())
-            .ok_or_else(|| MvpError::Custom("Project name is required".to_owned()))?
-            .trim_matches('"')
-            .to_string();
+            .map(|name| name.trim_matches('"').to_string())
+            .unwrap_or_default();
+        check_name(&project_name).map_err(|message| MvpError::InvalidArgs {
+            strategy: self.name().to_owned(),
+            message,
+        })?;
+
+        if plan.root().join(&project_name).exists() {
+            return Err(MvpError::ProjectCreation {
+                name: project_name,
+                reason: "destination already exists".to_owned(),
+            });
+        }
 
         let disable_vcs = values
             .get(1)
@@ -24,14 +38,35 @@ impl AddStrategy for InitStrategy {
             .map(|s| s.trim_matches('"') == "n")
             .unwrap_or(false);
 
-        create_project(&project_name, disable_vcs)?;
-        if disable_vcs {
-            return Ok(());
+        let archetype = context
+            .get("archetype")
+            .map(|v| tera::from_value::<Archetype>(v.clone()))
+            .transpose()
+            .map_err(|e| MvpError::Custom(format!("Invalid archetype: {e}")))?;
+
+        // 新项目里的文件都挂在项目目录下
+        let mut project = plan.child(&project_name);
+        match archetype {
+            Some(archetype) => {
+                create_from_archetype(tera, &mut project, &project_name, archetype)?;
+                if !disable_vcs {
+                    project.run("git", ["init", "--quiet"]);
+                }
+            }
+            None => create_project(plan, &project_name, disable_vcs),
         }
-        let composite = Composite::default();
-        println!("Adding init files...");
-        composite.handle(tera, context)?;
-        println!("Init files added.");
+        if !disable_vcs {
+            let composite = Composite::default();
+            report::progress(Message::AddingInitFiles);
+            let outer = context.remove("project");
+            let planned = planned_project(outer.as_ref(), &project_name, archetype);
+            context.insert("project", &planned);
+            composite.handle(tera, context, &mut project)?;
+            if let Some(outer) = outer {
+                context.insert("project", &outer);
+            }
+        }
+        plan.nest(&project_name, project);
         Ok(())
     }
     fn name(&self) -> &str {
@@ -39,26 +74,145 @@ impl AddStrategy for InitStrategy {
     }
 }
 
-fn create_project(project_name: &str, disable_vcs: bool) -> Result<(), MvpError> {
-    println!("Creating project: {}", project_name);
-    let mut cmd = Command::new("cargo");
-    cmd.arg("new").arg(project_name);
+/// Accepts names `cargo new` would: ASCII letters, digits, `-` and `_`, not
+/// starting with a digit. The name is also the directory, so no separators.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Project name is required".to_owned());
+    }
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!(
+            "`{name}` is not a valid package name: use letters, digits, `-` and `_`, \
+             not starting with a digit"
+        ));
+    }
+    Ok(())
+}
+
+fn create_project(plan: &mut Plan, project_name: &str, disable_vcs: bool) {
+    report::progress(Message::CreatingProject(project_name));
+    let mut args = vec!["new", project_name];
 
     if disable_vcs {
-        cmd.arg("--vcs").arg("none");
+        args.extend(["--vcs", "none"]);
     }
 
-    let status = cmd.status()?;
+    plan.run("cargo", args);
+}
 
-    if status.success() {
-        println!("Project '{}' created successfully!", project_name);
-        // 新增cd into the project directory
-        std::env::set_current_dir(project_name)?;
-        Ok(())
+/// Renders the archetype's templates instead of running `cargo new`, so the
+/// whole project shows up in `--dry-run`.
+fn create_from_archetype(
+    tera: &Tera,
+    project: &mut Plan,
+    project_name: &str,
+    archetype: Archetype,
+) -> Result<(), MvpError> {
+    report::progress(Message::CreatingArchetype {
+        archetype: archetype.dir_name(),
+        name: project_name,
+    });
+    let context = contexts::ArchetypeContext::new(project_name);
+    for (dest, template) in archetype.files() {
+        project.write(dest, context.render(tera, template)?);
+    }
+    Ok(())
+}
+
+/// `project` for the init files: the new project isn't on disk yet, so it is
+/// described from what `cargo new` or the archetype will create. Only the
+/// git user carries over from the surrounding directory.
+fn planned_project(
+    outer: Option<&tera::Value>,
+    name: &str,
+    archetype: Option<Archetype>,
+) -> Project {
+    let outer: Project = outer
+        .and_then(|v| tera::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let (lib, bin) = match archetype {
+        Some(archetype) => {
+            let files = archetype.files();
+            let has = |path: &str| files.iter().any(|(dest, _)| dest == Path::new(path));
+            (has("src/lib.rs"), has("src/main.rs"))
+        }
+        // `cargo new` 默认生成二进制包
+        None => (false, true),
+    };
+    let package = if archetype == Some(Archetype::Workspace) {
+        Package::default()
     } else {
-        Err(MvpError::Custom(format!(
-            "Failed to create project '{}'",
-            project_name
-        )))
+        Package {
+            name: name.to_owned(),
+            lib,
+            bins: bin.then(|| name.to_owned()).into_iter().collect(),
+            ..Default::default()
+        }
+    };
+    Project {
+        package,
+        git: Git {
+            remote: String::new(),
+            ..outer.git
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Generator, config::Config, plan::FileOp};
+
+    #[test]
+    fn init_archetype_renders_sources_instead_of_cargo_new() {
+        let mut context = Generator::base_context();
+        context.insert("init_values", &["demo-app"]);
+        context.insert("archetype", "lib");
+        let plan = Generator::builder()
+            .target("does-not-exist")
+            .config(Config::default())
+            .context(context)
+            .strategies(["init"])
+            .plan()
+            .unwrap();
+
+        let ops = plan.ops();
+        assert!(
+            !ops.iter()
+                .any(|op| matches!(op, FileOp::Run { program, .. } if program == "cargo"))
+        );
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            FileOp::Write { path, contents }
+                if path.ends_with("demo-app/tests/integration.rs") && contents.contains("use demo_app::")
+        )));
+        assert!(
+            ops.iter()
+                .any(|op| op.path().ends_with("demo-app/.gitignore"))
+        );
+    }
+
+    #[test]
+    fn init_rejects_missing_and_invalid_names() {
+        for values in [&[][..], &[""], &["../escape"], &["1st"], &["my app"]] {
+            let mut context = Generator::base_context();
+            context.insert("init_values", values);
+            let err = Generator::builder()
+                .target("does-not-exist")
+                .config(Config::default())
+                .context(context)
+                .strategies(["init"])
+                .plan()
+                .unwrap_err();
+            assert!(
+                matches!(err, MvpError::InvalidArgs { .. }),
+                "{values:?}: {err}"
+            );
+            assert_eq!(err.exit_code(), 64);
+        }
     }
 }
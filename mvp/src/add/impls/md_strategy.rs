@@ -1,18 +1,257 @@
-use std::fs;
+use std::{
+    env,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 
 use super::super::prelude::*;
+use crate::{
+    add::{
+        manifest::{Feature, Manifest},
+        regions,
+        rust_source::SourceFiles,
+    },
+    project::workspace_root,
+};
 
 pub struct MdStrategy;
 
-// Add README.md
+// Add README.md, or refresh its generated regions
 impl AddStrategy for MdStrategy {
-    fn handle(&self, tera: &Tera, context: &mut Context) -> Result<(), MvpError> {
-        let content = tera.render("README.md", context)?;
-        fs::write("README.md", content.as_bytes())?;
-        println!("Created README.md");
+    fn handle(&self, tera: &Tera, _context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let mut files = SourceFiles::new(plan.root());
+        let readme = readme_context(&mut files, plan.root())?;
+        let content = readme.render(tera, "README.md")?;
+
+        let readme_md = Path::new("README.md");
+        match files.read(readme_md)? {
+            // 已有 README：只刷新标记区域，手写的部分不动；没有标记就整个不动
+            Some(existing) => match regions::refresh(existing, &content) {
+                Some(refreshed) => files.set(readme_md, refreshed),
+                None => report::progress(Message::NoRegions("README.md")),
+            },
+            None => files.set(readme_md, content),
+        }
+        files.finish(plan);
         Ok(())
     }
     fn name(&self) -> &str {
         "md"
     }
 }
+
+/// Metadata from the target's `Cargo.toml`; before `cargo new` has run only
+/// the directory name is known.
+fn readme_context(
+    files: &mut SourceFiles,
+    root: &Path,
+) -> Result<contexts::ReadmeContext, MvpError> {
+    let fallback_name = std::path::absolute(root)?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let cargo_toml = Path::new("Cargo.toml");
+    let Some(text) = files.read(cargo_toml)? else {
+        return Ok(contexts::ReadmeContext {
+            name: fallback_name,
+            ..Default::default()
+        });
+    };
+    let manifest = Manifest::parse(cargo_toml, text)?;
+    let field = |key| manifest.package_field(key).unwrap_or_default().to_owned();
+
+    let features = manifest.features();
+    let defaults: Vec<String> = features
+        .iter()
+        .find(|f| f.name == "default")
+        .map(|f| f.enables.clone())
+        .unwrap_or_default();
+    let features = features
+        .into_iter()
+        .filter(|f| f.name != "default")
+        .map(
+            |Feature {
+                 name, description, ..
+             }| contexts::ReadmeFeature {
+                default: defaults.contains(&name),
+                name,
+                description,
+            },
+        )
+        .collect();
+
+    // 只有 clap 程序才能放心地用 --help 调用，别的程序可能忽略参数直接运行
+    let usage = manifest
+        .dependencies("dependencies")
+        .contains("clap")
+        .then(|| usage_binary(&manifest, root))
+        .flatten()
+        .and_then(|bin| built_binary(root, &bin))
+        .and_then(|bin| help_output(&bin))
+        .unwrap_or_default();
+
+    Ok(contexts::ReadmeContext {
+        name: manifest.package_name().map_or(fallback_name, str::to_owned),
+        description: field("description"),
+        repository: field("repository"),
+        license: field("license"),
+        msrv: field("rust-version"),
+        features,
+        usage,
+    })
+}
+
+/// The binary whose `--help` goes in the README: the one named after the
+/// package, else the first.
+fn usage_binary(manifest: &Manifest, root: &Path) -> Option<String> {
+    let mut bins = manifest.binary_names(root);
+    match bins
+        .iter()
+        .position(|bin| Some(bin.as_str()) == manifest.package_name())
+    {
+        Some(index) => Some(bins.swap_remove(index)),
+        None => bins.into_iter().next(),
+    }
+}
+
+/// How long `<bin> --help` may take before it is killed.
+const HELP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The most recently built `bin` in `CARGO_TARGET_DIR` or the `target/`
+/// directory of the crate or of its workspace.
+fn built_binary(root: &Path, bin: &str) -> Option<PathBuf> {
+    let root = std::path::absolute(root).ok()?;
+    let file_name = format!("{bin}{}", env::consts::EXE_SUFFIX);
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain([root.join("target")])
+        .chain(workspace_root(&root).map(|dir| dir.join("target")))
+        .flat_map(|target| {
+            ["release", "debug"].map(|profile| target.join(profile).join(&file_name))
+        })
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn help_output(bin: &Path) -> Option<String> {
+    report::debug(format!("Capturing usage from {} --help", bin.display()));
+    let mut child = Command::new(bin)
+        .arg("--help")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // 另起线程读 stdout，输出很长时子进程不会卡在写满的管道上
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut text = String::new();
+        stdout.read_to_string(&mut text).map(|_| text)
+    });
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if started.elapsed() > HELP_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            report::debug(format!("{} --help timed out", bin.display()));
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    let text = reader.join().ok()?.ok()?;
+    status.success().then(|| text.trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::plan::FileOp;
+
+    #[test]
+    fn existing_readme_keeps_its_prose() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\ndescription = \"Does things\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "<!-- mvp:begin header -->\n# old\n<!-- mvp:end header -->\n\nHand-written.\n",
+        )
+        .unwrap();
+
+        let mut plan = Plan::new(dir.path());
+        MdStrategy
+            .handle(&crate::templates::TEMPLATES, &mut Context::new(), &mut plan)
+            .unwrap();
+        assert_eq!(
+            plan.ops(),
+            [FileOp::Write {
+                path: "README.md".into(),
+                contents: "<!-- mvp:begin header -->\n# demo\n\nDoes things\n<!-- mvp:end header -->\n\nHand-written.\n".into()
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn usage_comes_from_the_package_binary_or_the_first() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        let manifest =
+            Manifest::parse(Path::new("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        assert_eq!(usage_binary(&manifest, root), None);
+
+        fs::write(root.join("src/bin/zed.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/bin/tool.rs"), "fn main() {}").unwrap();
+        assert_eq!(usage_binary(&manifest, root).as_deref(), Some("tool"));
+
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        assert_eq!(usage_binary(&manifest, root).as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn help_output_gives_up_on_programs_that_keep_running() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("server");
+        fs::write(&bin, "#!/bin/sh\nsleep 30\n").unwrap();
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let started = Instant::now();
+        assert_eq!(help_output(&bin), None);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn readme_without_regions_is_left_alone() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("README.md"), "# My own README\n").unwrap();
+
+        let mut plan = Plan::new(dir.path());
+        MdStrategy
+            .handle(&crate::templates::TEMPLATES, &mut Context::new(), &mut plan)
+            .unwrap();
+        assert!(plan.is_empty(), "{:?}", plan.ops());
+    }
+}
@@ -1,15 +1,39 @@
+mod bench_strategy;
+mod cargo_strategy;
+mod changelog_strategy;
+mod clippy_strategy;
+mod deny_strategy;
+mod devcontainer_strategy;
+mod docker_strategy;
+mod error_strategy;
 mod fmt_strategy;
 mod git_ignore_strategy;
+mod headers_strategy;
 mod init_strategy;
 mod lic_strategy;
 mod md_strategy;
+mod module_strategy;
+mod tests_strategy;
+mod toolchain_strategy;
 mod vscode_strategy;
 
 mod composite;
 
+pub use bench_strategy::BenchStrategy;
+pub use cargo_strategy::CargoStrategy;
+pub use changelog_strategy::ChangelogStrategy;
+pub use clippy_strategy::ClippyStrategy;
+pub use deny_strategy::DenyStrategy;
+pub use devcontainer_strategy::DevcontainerStrategy;
+pub use docker_strategy::DockerStrategy;
+pub use error_strategy::ErrorStrategy;
 pub use fmt_strategy::FmtStrategy;
 pub use git_ignore_strategy::GitIgnoreStrategy;
+pub use headers_strategy::HeadersStrategy;
 pub use init_strategy::InitStrategy;
 pub use lic_strategy::LicStrategy;
 pub use md_strategy::MdStrategy;
+pub use module_strategy::ModuleStrategy;
+pub use tests_strategy::TestsStrategy;
+pub use toolchain_strategy::ToolchainStrategy;
 pub use vscode_strategy::VscodeStrategy;
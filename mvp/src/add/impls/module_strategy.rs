@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+
+use super::super::prelude::*;
+use crate::add::rust_source::{SourceFiles, crate_root};
+
+pub struct ModuleStrategy;
+
+/// `mvp add module <path> [--pub] [--tests] [--mod-rs] [--use <ITEM>]..`
+#[derive(clap::Parser)]
+struct ModuleArgs {
+    /// Module path below the crate root, e.g. `add/impls/foo` or `add::impls::foo`
+    path: String,
+    /// Declare the module (and any missing parents) with `pub mod`
+    #[arg(long = "pub")]
+    public: bool,
+    /// Add a `#[cfg(test)] mod tests` block to the new file
+    #[arg(long)]
+    tests: bool,
+    /// Create `foo/mod.rs` instead of `foo.rs`
+    #[arg(long)]
+    mod_rs: bool,
+    /// Re-export ITEM from the new module in its parent with `pub use`
+    #[arg(long = "use", value_name = "ITEM")]
+    uses: Vec<String>,
+}
+
+// Add a module and declare it in its parent, creating missing parents
+impl AddStrategy for ModuleStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let args: ModuleArgs = parse_args(self.name(), context)?;
+        let segments: Vec<&str> = args
+            .path
+            .split(['/', ':'])
+            .filter(|s| !s.is_empty())
+            .collect();
+        if segments.is_empty() {
+            return Err(invalid(&args.path, "module path is empty"));
+        }
+        for segment in &segments {
+            syn::parse_str::<syn::Ident>(segment).map_err(|_| {
+                invalid(
+                    &args.path,
+                    &format!("`{segment}` is not a valid module name"),
+                )
+            })?;
+        }
+
+        let mut parent = crate_root(plan.root()).ok_or_else(|| {
+            MvpError::Custom(format!(
+                "No src/lib.rs or src/main.rs in {}",
+                plan.root().display()
+            ))
+        })?;
+        let mut files = SourceFiles::new(plan.root());
+        let mut dir = PathBuf::from("src");
+        for (i, segment) in segments.iter().enumerate() {
+            let leaf = i + 1 == segments.len();
+            files.declare_mod(&parent, segment, args.public)?;
+
+            // 已有的 foo.rs 或 foo/mod.rs 原样保留，只补声明
+            let flat = dir.join(format!("{segment}.rs"));
+            let nested = dir.join(segment).join("mod.rs");
+            let file = if files.exists(&flat)? {
+                flat
+            } else if files.exists(&nested)? {
+                nested
+            } else {
+                let file = if leaf && !args.mod_rs { flat } else { nested };
+                let content = if leaf {
+                    contexts::ModuleContext {
+                        name: segment.to_string(),
+                        tests: args.tests,
+                    }
+                    .render(tera, "module.rs")?
+                } else {
+                    String::new()
+                };
+                report::progress(Message::CreatingModule(&file));
+                files.set(&file, content);
+                file
+            };
+
+            if leaf {
+                for item in &args.uses {
+                    files.declare_use(&parent, &format!("{segment}::{item}"), true)?;
+                }
+            }
+            parent = file;
+            dir = dir.join(segment);
+        }
+        files.finish(plan);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "module"
+    }
+}
+
+fn invalid(path: &str, reason: &str) -> MvpError {
+    MvpError::InvalidArgs {
+        strategy: "module".to_owned(),
+        message: format!("{path}: {reason}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::plan::FileOp;
+
+    #[test]
+    fn nested_modules_are_created_and_wired() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/add")).unwrap();
+        fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub mod add;\npub mod error;\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("src/add/mod.rs"), "pub mod base;\n").unwrap();
+
+        let mut context = Context::new();
+        context.insert("args", &["add/impls/foo", "--tests", "--use", "Foo"]);
+        let mut plan = Plan::new(dir.path());
+        ModuleStrategy
+            .handle(&crate::templates::TEMPLATES, &mut context, &mut plan)
+            .unwrap();
+
+        let written: Vec<_> = plan
+            .ops()
+            .iter()
+            .map(|op| match op {
+                FileOp::Write { path, contents } => (path.to_str().unwrap(), contents.as_str()),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            written,
+            [
+                (
+                    "src/add/impls/foo.rs",
+                    "#[cfg(test)]\nmod tests {\n    #[test]\n    fn foo_works() {}\n}\n"
+                ),
+                ("src/add/impls/mod.rs", "mod foo;\n\npub use foo::Foo;\n"),
+                ("src/add/mod.rs", "pub mod base;\n\nmod impls;\n"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::path::{Path, PathBuf};
+
+use super::super::prelude::*;
+use crate::add::{
+    manifest::Manifest,
+    rust_source::{PublicFn, PublicItems, Returns, SourceFiles, parse_error, public_items},
+};
+
+pub struct TestsStrategy;
+
+/// `mvp add tests [name]`
+#[derive(clap::Parser)]
+struct TestsArgs {
+    /// Name of the test target, i.e. `tests/<name>.rs`
+    #[arg(default_value = "integration")]
+    name: String,
+}
+
+const TASKS_JSON: &str = ".vscode/tasks.json";
+
+// Add tests/common/mod.rs, a sample integration test and a VS Code test task
+impl AddStrategy for TestsStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let args: TestsArgs = parse_args(self.name(), context)?;
+        let mut files = SourceFiles::new(plan.root());
+        let test_rs = PathBuf::from(format!("tests/{}.rs", args.name));
+        if files.exists(&test_rs)? {
+            return Err(MvpError::FileExists {
+                path: plan.root().join(test_rs),
+            });
+        }
+
+        let cargo_toml = Path::new("Cargo.toml");
+        let text = files.read(cargo_toml)?.ok_or_else(|| {
+            MvpError::Custom(format!("No Cargo.toml in {}", plan.root().display()))
+        })?;
+        let crate_name = Manifest::parse(cargo_toml, text)?
+            .package_name()
+            .ok_or_else(|| MvpError::Custom("Cargo.toml has no [package] name".to_owned()))?
+            .replace('-', "_");
+        let functions = public_functions(&mut files)?
+            .into_iter()
+            .map(|(path, function)| contexts::TestFunction {
+                test_name: format!("{}_works", path.replace("::", "_")),
+                path,
+                args: vec!["Default::default()"; function.args].join(", "),
+                returns: function.returns != Returns::Nothing,
+                comparable: function.returns == Returns::Comparable,
+            })
+            .collect();
+
+        let tests = contexts::TestsContext {
+            name: args.name,
+            crate_name,
+            functions,
+        };
+        // common 模块可能已经被别的测试用着，存在就不动
+        let common = Path::new("tests/common/mod.rs");
+        if !files.exists(common)? {
+            files.set(common, tests.render(tera, "tests/common/mod.rs")?);
+        }
+        files.set(&test_rs, tests.render(tera, "tests/integration.rs")?);
+
+        let task = tests.render(tera, "vscode/test-task.json")?;
+        let tasks_json = Path::new(TASKS_JSON);
+        let tasks = match files.read(tasks_json)? {
+            Some(existing) => add_task(existing, &task, &tests.name),
+            None => Some(format!(
+                "{{\n    \"version\": \"2.0.0\",\n    \"tasks\": [\n{task}    ]\n}}\n"
+            )),
+        };
+        match tasks {
+            Some(tasks) => files.set(tasks_json, tasks),
+            None => report::progress(Message::Unchanged(TASKS_JSON)),
+        }
+        files.finish(plan);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "tests"
+    }
+}
+
+/// Public functions reachable from `src/lib.rs`, directly, through its
+/// `pub mod` files or re-exported with `pub use`, as paths relative to the
+/// crate.
+fn public_functions(files: &mut SourceFiles) -> Result<Vec<(String, PublicFn)>, MvpError> {
+    let lib_rs = Path::new("src/lib.rs");
+    let text = files.read(lib_rs)?.ok_or_else(|| {
+        MvpError::Custom("Integration tests need a library target (src/lib.rs)".to_owned())
+    })?;
+    let root = public_items(text).map_err(|e| parse_error(lib_rs, e))?;
+    let mut functions: Vec<_> = root
+        .functions
+        .into_iter()
+        .map(|f| (f.name.clone(), f))
+        .collect();
+    for module in &root.modules {
+        if let Some(items) = module_items(files, module)? {
+            functions.extend(
+                items
+                    .functions
+                    .into_iter()
+                    .map(|f| (format!("{module}::{}", f.name), f)),
+            );
+        }
+    }
+    // `pub use imp::run;` 导出的才是库真正的接口，imp 本身可能是私有模块
+    for (source, exported) in &root.reexports {
+        let source = source
+            .strip_prefix("crate::")
+            .or_else(|| source.strip_prefix("self::"))
+            .unwrap_or(source);
+        let Some((module, name)) = source.split_once("::") else {
+            continue;
+        };
+        if name.contains("::") {
+            continue;
+        }
+        let Some(items) = module_items(files, module)? else {
+            continue;
+        };
+        for function in items.functions {
+            let path = match name {
+                "*" => function.name.clone(),
+                _ if function.name == name => exported.clone(),
+                _ => continue,
+            };
+            if !functions.iter().any(|(existing, _)| *existing == path) {
+                functions.push((path, function));
+            }
+        }
+    }
+    Ok(functions)
+}
+
+/// Public items of `src/<module>.rs` or `src/<module>/mod.rs`.
+fn module_items(files: &mut SourceFiles, module: &str) -> Result<Option<PublicItems>, MvpError> {
+    for path in [
+        PathBuf::from(format!("src/{module}.rs")),
+        PathBuf::from(format!("src/{module}/mod.rs")),
+    ] {
+        if let Some(text) = files.read(&path)? {
+            return public_items(text)
+                .map(Some)
+                .map_err(|e| parse_error(&path, e));
+        }
+    }
+    Ok(None)
+}
+
+/// Inserts `task` at the start of the `"tasks"` array of a (JSON with
+/// comments) tasks file, or `None` if a task with the same label exists or
+/// there is no such array.
+fn add_task(existing: &str, task: &str, name: &str) -> Option<String> {
+    if existing.contains(&format!("\"label\": \"Test {name}\"")) {
+        return None;
+    }
+    let key = existing.find("\"tasks\"")?;
+    let open = key + existing[key..].find('[')?;
+    let rest = &existing[open + 1..];
+    let separator = if rest.trim_start().starts_with(']') {
+        ""
+    } else {
+        ","
+    };
+    let body = task.trim_end_matches('\n');
+    Some(format!(
+        "{}\n{body}{separator}{}",
+        &existing[..=open],
+        rest.strip_prefix('\n')
+            .map_or(rest.to_owned(), |r| format!("\n{r}"))
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::plan::FileOp;
+
+    #[test]
+    fn sample_tests_call_what_the_crate_exports() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-lib\"\n").unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "mod imp;\npub use imp::greet;\n\npub fn add(a: i32, b: i32) -> i32 { a + b }\n\
+             pub fn reset() {}\npub(crate) fn hidden() {}\n\
+             pub fn open(path: &std::path::Path) {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/imp.rs"),
+            "pub fn greet(name: &str) -> String { name.into() }\npub fn other() {}\n",
+        )
+        .unwrap();
+
+        let mut context = Context::new();
+        let mut plan = Plan::new(root);
+        TestsStrategy
+            .handle(&crate::templates::TEMPLATES, &mut context, &mut plan)
+            .unwrap();
+        let test_rs = plan
+            .ops()
+            .iter()
+            .find_map(|op| match op {
+                FileOp::Write { path, contents } if path.ends_with("integration.rs") => {
+                    Some(contents)
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(
+            test_rs.contains(
+                "fn add_works() {\n    let _result = my_lib::add(Default::default(), Default::default());\n    \
+                 // Fill in what `add` should return, then drop the `_` above:\n    \
+                 // assert_eq!(result, <expected>);\n}"
+            ),
+            "{test_rs}"
+        );
+        assert_eq!(test_rs.matches("CwdGuard::enter").count(), 1, "{test_rs}");
+        assert!(test_rs.contains("\n    my_lib::reset();\n"), "{test_rs}");
+        assert!(test_rs.contains("fn greet_works()"), "{test_rs}");
+        for missing in ["hidden", "open", "other", "imp::"] {
+            assert!(!test_rs.contains(missing), "{missing} in {test_rs}");
+        }
+    }
+
+    #[test]
+    fn test_task_goes_first_in_existing_tasks() {
+        let existing = "{\n    // comment\n    \"tasks\": [\n        {\n            \"label\": \"Build\"\n        }\n    ]\n}\n";
+        let task = "        {\n            \"label\": \"Test it\"\n        }\n";
+        assert_eq!(
+            add_task(existing, task, "it").unwrap(),
+            "{\n    // comment\n    \"tasks\": [\n        {\n            \"label\": \"Test it\"\n        },\n        {\n            \"label\": \"Build\"\n        }\n    ]\n}\n"
+        );
+        assert_eq!(
+            add_task("{ \"tasks\": [] }", task, "it").unwrap(),
+            format!("{{ \"tasks\": [\n{}] }}", task.trim_end())
+        );
+        let added = add_task(existing, task, "it").unwrap();
+        assert_eq!(add_task(&added, task, "it"), None);
+    }
+}
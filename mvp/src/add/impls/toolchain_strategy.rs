@@ -0,0 +1,46 @@
+use super::super::prelude::*;
+
+pub struct ToolchainStrategy;
+
+// Pin the toolchain in rust-toolchain.toml
+impl AddStrategy for ToolchainStrategy {
+    fn handle(&self, tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let content = contexts::ToolchainContext::from_context(context)?
+            .render(tera, "rust-toolchain.toml")?;
+        plan.write("rust-toolchain.toml", content);
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        "toolchain"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Generator, config::Config, plan::FileOp};
+
+    #[test]
+    fn toolchain_follows_config() {
+        let config: Config = toml::from_str(
+            "[toolchain]\nchannel = \"1.89\"\ntargets = [\"wasm32-unknown-unknown\"]\n",
+        )
+        .unwrap();
+        let plan = Generator::builder()
+            .config(config)
+            .strategies(["toolchain"])
+            .plan()
+            .unwrap();
+
+        let [FileOp::Write { path, contents }] = plan.ops() else {
+            panic!("{:?}", plan.ops());
+        };
+        assert_eq!(path.to_str(), Some("rust-toolchain.toml"));
+        let toml: toml::Table = toml::from_str(contents).unwrap();
+        assert_eq!(toml["toolchain"]["channel"].as_str(), Some("1.89"));
+        assert_eq!(toml["toolchain"]["components"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            toml["toolchain"]["targets"].as_array().unwrap()[0].as_str(),
+            Some("wasm32-unknown-unknown")
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::{collections::BTreeSet, path::Path};
+
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Table, value};
+
+use crate::error::MvpError;
+
+/// A `Cargo.toml` edited in place, keeping comments and formatting.
+pub struct Manifest {
+    doc: DocumentMut,
+}
+
+impl Manifest {
+    pub fn parse(path: &Path, text: &str) -> Result<Self, MvpError> {
+        text.parse()
+            .map(|doc| Self { doc })
+            .map_err(|e| MvpError::Custom(format!("Failed to parse {}: {e}", path.display())))
+    }
+
+    /// `[package] name`.
+    pub fn package_name(&self) -> Option<&str> {
+        self.package_field("name")
+    }
+
+    /// A string field of `[package]`, e.g. `description` or `rust-version`;
+    /// `None` when missing or inherited from the workspace.
+    pub fn package_field(&self, key: &str) -> Option<&str> {
+        self.doc.get("package")?.get(key)?.as_str()
+    }
+
+    /// A string field of `[workspace.package]`, which members inherit with
+    /// `key.workspace = true`.
+    pub fn workspace_package_field(&self, key: &str) -> Option<&str> {
+        self.doc
+            .get("workspace")?
+            .get("package")?
+            .get(key)?
+            .as_str()
+    }
+
+    /// `[package] version`, or `[workspace.package] version` for a virtual
+    /// manifest.
+    pub fn version(&self) -> Option<&str> {
+        self.package_field("version")
+            .or_else(|| self.workspace_package_field("version"))
+    }
+
+    /// Whether `[package]` takes its version from the workspace.
+    pub fn inherits_version(&self) -> bool {
+        self.doc
+            .get("package")
+            .and_then(|p| p.get("version"))
+            .and_then(|v| v.get("workspace"))
+            .and_then(Item::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Replaces the version returned by [`Manifest::version`], keeping any
+    /// trailing comment.
+    pub fn set_version(&mut self, version: &str) -> bool {
+        let item = match self.package_field("version") {
+            Some(_) => self
+                .doc
+                .get_mut("package")
+                .and_then(|p| p.get_mut("version")),
+            None => self
+                .doc
+                .get_mut("workspace")
+                .and_then(|w| w.get_mut("package"))
+                .and_then(|p| p.get_mut("version")),
+        };
+        item.is_some_and(|item| set_str(item, version))
+    }
+
+    /// `[workspace] members`, as written (globs included).
+    pub fn workspace_members(&self) -> Vec<String> {
+        self.workspace_list("members")
+    }
+
+    /// `[workspace] exclude`: directories that are never members.
+    pub fn workspace_excludes(&self) -> Vec<String> {
+        self.workspace_list("exclude")
+    }
+
+    fn workspace_list(&self, key: &str) -> Vec<String> {
+        self.doc
+            .get("workspace")
+            .and_then(|w| w.get(key))
+            .and_then(Item::as_array)
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets the `version` requirement of every `name = { path = .., version =
+    /// .. }` dependency, including `[workspace.dependencies]`. Returns whether
+    /// anything changed.
+    pub fn set_dependency_version(&mut self, name: &str, version: &str) -> bool {
+        let mut changed = false;
+        let tables = ["dependencies", "dev-dependencies", "build-dependencies"];
+        let mut items: Vec<&mut Item> = Vec::new();
+        for (key, item) in self.doc.iter_mut() {
+            if key.get() == "workspace" {
+                items.extend(item.get_mut("dependencies"));
+            } else if tables.contains(&key.get()) {
+                items.push(item);
+            }
+        }
+        for deps in items {
+            let Some(dep) = deps.get_mut(name).and_then(|d| d.as_table_like_mut()) else {
+                continue;
+            };
+            if dep.contains_key("path")
+                && let Some(item) = dep.get_mut("version")
+            {
+                changed |= set_str(item, version);
+            }
+        }
+        changed
+    }
+
+    /// Every `[features]` entry with what it enables and the comment lines
+    /// right above it.
+    pub fn features(&self) -> Vec<Feature> {
+        let Some(features) = self.doc.get("features").and_then(Item::as_table) else {
+            return Vec::new();
+        };
+        features
+            .iter()
+            .map(|(name, item)| {
+                let comment = features
+                    .key(name)
+                    .and_then(|key| key.leaf_decor().prefix())
+                    .and_then(|prefix| prefix.as_str())
+                    .unwrap_or_default();
+                Feature {
+                    name: name.to_owned(),
+                    enables: item
+                        .as_array()
+                        .map(|a| {
+                            a.iter()
+                                .filter_map(|v| v.as_str())
+                                .map(str::to_owned)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    description: comment
+                        .lines()
+                        .filter_map(|line| line.trim().strip_prefix('#'))
+                        .map(|line| line.trim_start_matches('#').trim())
+                        .filter(|line| !line.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the package has a library target: `src/lib.rs` or a `[lib]`.
+    pub fn has_lib(&self, root: &Path) -> bool {
+        self.doc.contains_key("lib") || root.join("src/lib.rs").is_file()
+    }
+
+    /// Every binary target: `[[bin]]` entries, `src/main.rs` and
+    /// `src/bin/*.rs`, sorted.
+    pub fn binary_names(&self, root: &Path) -> Vec<String> {
+        let mut names: BTreeSet<String> = self
+            .doc
+            .get("bin")
+            .and_then(Item::as_array_of_tables)
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|bin| bin.get("name")?.as_str())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if root.join("src/main.rs").is_file() {
+            names.extend(self.package_name().map(str::to_owned));
+        }
+        if let Ok(entries) = std::fs::read_dir(root.join("src/bin")) {
+            for path in entries.flatten().map(|e| e.path()) {
+                let name = if path.is_dir() && path.join("main.rs").is_file() {
+                    path.file_name()
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    path.file_stem()
+                } else {
+                    None
+                };
+                names.extend(name.map(|n| n.to_string_lossy().into_owned()));
+            }
+        }
+        names.into_iter().collect()
+    }
+
+    /// Names in `table`, e.g. `dependencies` or `dev-dependencies`.
+    pub fn dependencies(&self, table: &str) -> BTreeSet<String> {
+        self.doc
+            .get(table)
+            .and_then(Item::as_table_like)
+            .map(|deps| deps.iter().map(|(name, _)| name.to_owned()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds `name = "version"` to `table` unless it is already listed there.
+    /// Returns whether the manifest changed.
+    pub fn add_dependency(&mut self, table: &str, name: &str, version: &str) -> bool {
+        let deps = self
+            .doc
+            .entry(table)
+            .or_insert_with(|| Item::Table(Table::new()));
+        let Some(deps) = deps.as_table_like_mut() else {
+            return false;
+        };
+        if deps.contains_key(name) {
+            return false;
+        }
+        deps.insert(name, value(version));
+        true
+    }
+
+    /// Appends a `[[key]]` entry named `name` with the extra `fields`, unless
+    /// one with that name exists. An inline `key = [{ … }]` array gets an
+    /// inline table instead. Returns whether the manifest changed.
+    pub fn add_target(
+        &mut self,
+        key: &str,
+        name: &str,
+        fields: &[(&str, toml_edit::Value)],
+    ) -> Result<bool, MvpError> {
+        let targets = self
+            .doc
+            .entry(key)
+            .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()));
+        if let Some(targets) = targets.as_array_of_tables_mut() {
+            if targets
+                .iter()
+                .any(|t| t.get("name").and_then(Item::as_str) == Some(name))
+            {
+                return Ok(false);
+            }
+            let mut table = Table::new();
+            table.insert("name", value(name));
+            for (field, v) in fields {
+                table.insert(field, value(v.clone()));
+            }
+            targets.push(table);
+            return Ok(true);
+        }
+        let Some(targets) = targets.as_array_mut() else {
+            return Err(MvpError::Custom(format!(
+                "`{key}` in Cargo.toml is neither `[[{key}]]` tables nor an array"
+            )));
+        };
+        let mut named = false;
+        for target in targets.iter() {
+            let Some(target) = target.as_inline_table() else {
+                return Err(MvpError::Custom(format!(
+                    "`{key}` in Cargo.toml holds something other than tables"
+                )));
+            };
+            named |= target.get("name").and_then(|n| n.as_str()) == Some(name);
+        }
+        if named {
+            return Ok(false);
+        }
+        let mut table = InlineTable::new();
+        table.insert("name", name.into());
+        for (field, v) in fields {
+            table.insert(*field, v.clone());
+        }
+        targets.push(table);
+        Ok(true)
+    }
+}
+
+/// Replaces a string value in place, keeping its surrounding whitespace and
+/// comments.
+fn set_str(item: &mut Item, new: &str) -> bool {
+    let Some(value) = item.as_value_mut() else {
+        return false;
+    };
+    if value.as_str() == Some(new) {
+        return false;
+    }
+    let decor = value.decor().clone();
+    *value = new.into();
+    *value.decor_mut() = decor;
+    true
+}
+
+/// A `[features]` entry; `description` comes from `#` or `##` comments above
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feature {
+    pub name: String,
+    pub enables: Vec<String>,
+    pub description: String,
+}
+
+impl std::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.doc.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_keep_existing_formatting() {
+        let text = "[package]\nname = \"demo\" # the name\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n";
+        let mut manifest = Manifest::parse(Path::new("Cargo.toml"), text).unwrap();
+        assert_eq!(manifest.package_name(), Some("demo"));
+        assert!(manifest.add_dependency("dependencies", "thiserror", "2"));
+        assert!(!manifest.add_dependency("dependencies", "serde", "1"));
+        assert!(manifest.add_dependency("dev-dependencies", "criterion", "0.5"));
+        assert_eq!(
+            manifest.to_string(),
+            "[package]\nname = \"demo\" # the name\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nthiserror = \"2\"\n\n[dev-dependencies]\ncriterion = \"0.5\"\n"
+        );
+        assert!(
+            manifest
+                .add_target("bench", "sum", &[("harness", false.into())])
+                .unwrap()
+        );
+        assert!(!manifest.add_target("bench", "sum", &[]).unwrap());
+        assert!(
+            manifest
+                .to_string()
+                .ends_with("[[bench]]\nname = \"sum\"\nharness = false\n")
+        );
+        assert_eq!(manifest.package_field("description"), None);
+        assert_eq!(
+            manifest.dependencies("dependencies"),
+            BTreeSet::from(["serde".to_owned(), "thiserror".to_owned()])
+        );
+    }
+
+    #[test]
+    fn inline_target_arrays_are_extended() {
+        let text = "bench = [{ name = \"old\" }]\n\n[package]\nname = \"demo\"\n";
+        let mut manifest = Manifest::parse(Path::new("Cargo.toml"), text).unwrap();
+        assert!(!manifest.add_target("bench", "old", &[]).unwrap());
+        assert!(
+            manifest
+                .add_target("bench", "sum", &[("harness", false.into())])
+                .unwrap()
+        );
+        assert_eq!(
+            manifest.to_string(),
+            "bench = [{ name = \"old\" }, { name = \"sum\", harness = false }]\n\n[package]\nname = \"demo\"\n"
+        );
+
+        let mut manifest = Manifest::parse(Path::new("Cargo.toml"), "bench = \"x\"\n").unwrap();
+        assert!(manifest.add_target("bench", "sum", &[]).is_err());
+    }
+
+    #[test]
+    fn features_carry_their_comments() {
+        let text = "[package]\nname = \"demo\"\n\n[features]\ndefault = [\"std\"]\n## Use the standard library\nstd = []\n# Serde support\n# for all types\nserde = [\"dep:serde\"]\n";
+        let manifest = Manifest::parse(Path::new("Cargo.toml"), text).unwrap();
+        let features = manifest.features();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0].enables, ["std"]);
+        assert_eq!(features[1].description, "Use the standard library");
+        assert_eq!(features[2].description, "Serde support for all types");
+    }
+
+    #[test]
+    fn versions_are_bumped_in_place() {
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\" # keep me\n\n[dependencies]\ncore = { path = \"../core\", version = \"0.1.0\" }\nserde = \"1\"\n";
+        let mut manifest = Manifest::parse(Path::new("Cargo.toml"), text).unwrap();
+        assert_eq!(manifest.version(), Some("0.1.0"));
+        assert!(manifest.set_version("0.2.0"));
+        assert!(manifest.set_dependency_version("core", "0.2.0"));
+        assert!(!manifest.set_dependency_version("serde", "2"));
+        assert_eq!(manifest.to_string(), text.replace("0.1.0", "0.2.0"));
+    }
+}
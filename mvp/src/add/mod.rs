@@ -1,4 +1,11 @@
-mod base;
+pub mod archetype;
+pub mod base;
+pub mod config_strategy;
 pub mod context;
+pub mod hooks;
 pub mod impls;
+pub mod manifest;
+pub mod plugin;
 pub mod prelude;
+pub mod regions;
+pub mod rust_source;
@@ -0,0 +1,238 @@
+use std::{
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+use serde::Serialize;
+use tera::Value;
+
+use super::prelude::*;
+use crate::{config::config_dir, plan::FileOp};
+
+/// Executables named `mvp-strategy-<name>` act as external strategies.
+pub const PLUGIN_PREFIX: &str = "mvp-strategy-";
+
+/// What a plugin receives as JSON on stdin.
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    name: &'a str,
+    context: Value,
+    target: PathBuf,
+    options: Vec<String>,
+}
+
+/// A strategy backed by an external executable, in the spirit of cargo
+/// subcommands.
+///
+/// The plugin reads a [`PluginRequest`] from stdin and prints a JSON array of
+/// `write`/`append` operations, which go through the same plan as built-in
+/// strategies so `--dry-run` and `--on-conflict` apply to them too.
+pub struct PluginStrategy {
+    name: String,
+    path: PathBuf,
+}
+
+impl PluginStrategy {
+    /// Looks for `mvp-strategy-<name>` in `<config dir>/plugins`, then on
+    /// `PATH`.
+    pub fn discover(name: &str) -> Option<Self> {
+        Self::find_in(name, plugin_dirs())
+    }
+
+    pub fn find_in(name: &str, dirs: impl IntoIterator<Item = PathBuf>) -> Option<Self> {
+        let file_name = format!("{PLUGIN_PREFIX}{name}{}", env::consts::EXE_SUFFIX);
+        dirs.into_iter()
+            .map(|dir| dir.join(&file_name))
+            .find(|path| is_executable(path))
+            .map(|path| Self {
+                name: name.to_owned(),
+                path,
+            })
+    }
+
+    /// Every `mvp-strategy-*` executable in `dirs`; earlier directories
+    /// shadow later ones, as in [`PluginStrategy::find_in`].
+    pub fn installed(dirs: impl IntoIterator<Item = PathBuf>) -> Vec<Self> {
+        let mut plugins: Vec<Self> = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                let Some(name) = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix(PLUGIN_PREFIX))
+                    .map(|n| n.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(n))
+                else {
+                    continue;
+                };
+                if !name.is_empty()
+                    && is_executable(&path)
+                    && !plugins.iter().any(|p| p.name == name)
+                {
+                    plugins.push(Self {
+                        name: name.to_owned(),
+                        path,
+                    });
+                }
+            }
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AddStrategy for PluginStrategy {
+    fn handle(&self, _tera: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+        let options = context
+            .get("args")
+            .and_then(|v| tera::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let request = PluginRequest {
+            name: &self.name,
+            context: context.clone().into_json(),
+            target: std::path::absolute(plan.root())?,
+            options,
+        };
+
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = serde_json::to_vec(&request)
+            .map_err(|e| MvpError::Custom(format!("Failed to encode plugin request: {e}")))?;
+        // 插件可能先写满 stdout 再读 stdin，在这里同步写请求会互相卡死
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        // 不读完请求就退出的插件只会让写入遇到 broken pipe，以它的输出为准
+        match writer.join().expect("stdin writer panicked") {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+        if !output.status.success() {
+            return Err(MvpError::Custom(format!(
+                "Plugin {} failed with {}",
+                self.path.display(),
+                output.status
+            )));
+        }
+
+        let ops: Vec<FileOp> = serde_json::from_slice(&output.stdout).map_err(|e| {
+            MvpError::Custom(format!(
+                "Plugin {} returned invalid operations: {e}",
+                self.path.display()
+            ))
+        })?;
+        for op in ops {
+            if let FileOp::Run { program, .. } = &op {
+                return Err(MvpError::Custom(format!(
+                    "Plugin {} may not run commands (`{program}`)",
+                    self.path.display()
+                )));
+            }
+            plan.push(op);
+        }
+        Ok(())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Directories searched for plugins, in priority order.
+pub fn plugin_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<_> = config_dir()
+        .map(|dir| dir.join("plugins"))
+        .into_iter()
+        .collect();
+    if let Some(path) = env::var_os("PATH") {
+        dirs.extend(env::split_paths(&path));
+    }
+    dirs
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn plugin_output_becomes_plan_operations() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("mvp-strategy-hello");
+        fs::write(
+            &script,
+            "#!/bin/sh\ncat > /dev/null\necho '[{\"op\":\"write\",\"path\":\"hello.txt\",\"contents\":\"hi\"}]'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let plugin = PluginStrategy::find_in("hello", [dir.path().to_path_buf()]).unwrap();
+        assert!(PluginStrategy::find_in("missing", [dir.path().to_path_buf()]).is_none());
+        let installed = PluginStrategy::installed([dir.path().to_path_buf()]);
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].name(), "hello");
+
+        let mut plan = Plan::new(dir.path());
+        plugin
+            .handle(&Tera::default(), &mut Context::new(), &mut plan)
+            .unwrap();
+        assert_eq!(
+            plan.ops(),
+            [FileOp::Write {
+                path: "hello.txt".into(),
+                contents: "hi".into()
+            }]
+        );
+    }
+    #[test]
+    fn plugins_may_write_before_reading_the_request() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("mvp-strategy-chatty");
+        fs::write(
+            &script,
+            "#!/bin/sh\nprintf '[{\"op\":\"write\",\"path\":\"big.txt\",\"contents\":\"'\n\
+             head -c 200000 /dev/zero | tr '\\0' a\nprintf '\"}]'\ncat > /dev/null\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // 请求和输出都超过管道缓冲区
+        let mut context = Context::new();
+        context.insert("padding", &"x".repeat(200_000));
+        let plugin = PluginStrategy::find_in("chatty", [dir.path().to_path_buf()]).unwrap();
+        let mut plan = Plan::new(dir.path());
+        plugin
+            .handle(&Tera::default(), &mut context, &mut plan)
+            .unwrap();
+        let [FileOp::Write { contents, .. }] = plan.ops() else {
+            panic!("{:?}", plan.ops());
+        };
+        assert_eq!(contents.len(), 200_000);
+    }
+}
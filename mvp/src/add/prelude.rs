@@ -1,3 +1,10 @@
 pub use tera::{Context, Tera};
 
-pub use crate::{add::base::AddStrategy, error::MvpError};
+pub use crate::{
+    add::base::{AddStrategy, parse_args},
+    error::MvpError,
+    i18n::Message,
+    plan::Plan,
+    report,
+    templates::contexts::{self, TemplateContext},
+};
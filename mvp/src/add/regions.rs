@@ -0,0 +1,79 @@
+//! Generated regions inside otherwise hand-written files.
+//!
+//! A region sits between `<!-- mvp:begin NAME -->` and `<!-- mvp:end NAME -->`
+//! lines; refreshing a file replaces what is between the markers and leaves
+//! everything else alone.
+
+fn begin(name: &str) -> String {
+    format!("<!-- mvp:begin {name} -->")
+}
+
+fn end(name: &str) -> String {
+    format!("<!-- mvp:end {name} -->")
+}
+
+/// Names of the regions in `text`, in order.
+pub fn names(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("<!-- mvp:begin "))
+        .filter_map(|rest| rest.strip_suffix("-->"))
+        .map(str::trim)
+        .collect()
+}
+
+/// The contents of region `name`, without the marker lines.
+pub fn get<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let (start, end) = span(text, name)?;
+    Some(&text[start..end])
+}
+
+/// Byte range between the end of the begin marker line and the start of
+/// the end marker line.
+fn span(text: &str, name: &str) -> Option<(usize, usize)> {
+    let begin = begin(name);
+    let start = text.find(&begin)? + begin.len();
+    let start = start
+        + text[start..]
+            .find('\n')
+            .map_or(text.len() - start, |i| i + 1);
+    let end = start + text[start..].find(&end(name))?;
+    let end = text[..end].rfind('\n').map_or(start, |i| i + 1).max(start);
+    Some((start, end))
+}
+
+/// Replaces each region of `existing` with the same region of `rendered`.
+///
+/// Returns `None` when `existing` has no regions at all. Regions missing
+/// from either side are left as they are, so deleting a region from the file
+/// opts out of it.
+pub fn refresh(existing: &str, rendered: &str) -> Option<String> {
+    let names = names(existing);
+    if names.is_empty() {
+        return None;
+    }
+    let mut text = existing.to_owned();
+    for name in names {
+        let (Some(fresh), Some((start, end))) = (get(rendered, name), span(&text, name)) else {
+            continue;
+        };
+        text.replace_range(start..end, fresh);
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_only_touches_regions() {
+        let existing = "<!-- mvp:begin header -->\n# old\n<!-- mvp:end header -->\n\nMy own words.\n\n<!-- mvp:begin usage -->\nold usage\n<!-- mvp:end usage -->\n";
+        let rendered = "<!-- mvp:begin header -->\n# new\n\nA description\n<!-- mvp:end header -->\n\nTemplate words.\n\n<!-- mvp:begin usage -->\n<!-- mvp:end usage -->\n";
+        assert_eq!(names(existing), ["header", "usage"]);
+        assert_eq!(
+            refresh(existing, rendered).unwrap(),
+            "<!-- mvp:begin header -->\n# new\n\nA description\n<!-- mvp:end header -->\n\nMy own words.\n\n<!-- mvp:begin usage -->\n<!-- mvp:end usage -->\n"
+        );
+        assert_eq!(refresh("# hand written\n", rendered), None);
+    }
+}
@@ -0,0 +1,449 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use syn::{Item, UseTree, Visibility, spanned::Spanned};
+
+use crate::{error::MvpError, plan::Plan};
+
+/// `src/lib.rs`, or `src/main.rs` for binary crates, under `root`.
+pub fn crate_root(root: &Path) -> Option<PathBuf> {
+    ["src/lib.rs", "src/main.rs"]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|path| root.join(path).is_file())
+}
+
+/// Files a strategy reads and edits while planning, relative to the plan
+/// root. Edits stay in memory until [`SourceFiles::finish`] turns the changed
+/// files into plan operations.
+pub struct SourceFiles {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, (Option<String>, String)>,
+}
+
+impl SourceFiles {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Current contents of `path`, or `None` if it exists neither on disk nor
+    /// in a pending edit.
+    pub fn read(&mut self, path: &Path) -> Result<Option<&str>, MvpError> {
+        if !self.files.contains_key(path) {
+            match fs::read_to_string(self.root.join(path)) {
+                Ok(text) => {
+                    self.files
+                        .insert(path.to_path_buf(), (Some(text.clone()), text));
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(self.files.get(path).map(|(_, text)| text.as_str()))
+    }
+
+    pub fn exists(&mut self, path: &Path) -> Result<bool, MvpError> {
+        Ok(self.read(path)?.is_some())
+    }
+
+    pub fn set(&mut self, path: &Path, text: String) {
+        match self.files.get_mut(path) {
+            Some((_, current)) => *current = text,
+            None => {
+                self.files.insert(path.to_path_buf(), (None, text));
+            }
+        }
+    }
+
+    /// Records a write for every file whose contents changed.
+    pub fn finish(self, plan: &mut Plan) {
+        for (path, (original, text)) in self.files {
+            if original.as_deref() != Some(text.as_str()) {
+                plan.write(path, text);
+            }
+        }
+    }
+
+    /// Declares `mod name;` in the file at `path`; see [`insert_mod`].
+    pub fn declare_mod(&mut self, path: &Path, name: &str, public: bool) -> Result<(), MvpError> {
+        let text = self.read(path)?.unwrap_or_default().to_owned();
+        if let Some(text) = insert_mod(&text, name, public).map_err(|e| parse_error(path, e))? {
+            self.set(path, text);
+        }
+        Ok(())
+    }
+
+    /// Adds `use path;` to the file at `path`; see [`insert_use`].
+    pub fn declare_use(&mut self, path: &Path, item: &str, public: bool) -> Result<(), MvpError> {
+        let text = self.read(path)?.unwrap_or_default().to_owned();
+        if let Some(text) = insert_use(&text, item, public).map_err(|e| parse_error(path, e))? {
+            self.set(path, text);
+        }
+        Ok(())
+    }
+}
+
+/// Public items at the top level of a file, as found by [`public_items`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PublicItems {
+    /// `pub fn`s an integration test can call: not generic, not `async` or
+    /// `unsafe`, and every argument has a `Default` value.
+    pub functions: Vec<PublicFn>,
+    /// `pub mod name;` declarations whose contents live in another file.
+    pub modules: Vec<String>,
+    /// `pub use` re-exports as `(source path, exported name)`, e.g.
+    /// `("imp::run", "run")`; a glob is `("imp::*", "*")`.
+    pub reexports: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicFn {
+    pub name: String,
+    pub args: usize,
+    pub returns: Returns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Returns {
+    Nothing,
+    /// A type that is `PartialEq + Debug` for sure, such as `String` or
+    /// `Option<u32>`.
+    Comparable,
+    Other,
+}
+
+pub fn public_items(text: &str) -> syn::Result<PublicItems> {
+    let file = syn::parse_file(text)?;
+    let mut items = PublicItems::default();
+    for item in &file.items {
+        match item {
+            Item::Fn(f) if is_public(&f.vis) => items.functions.extend(callable(&f.sig)),
+            Item::Mod(m) if is_public(&m.vis) && m.content.is_none() => {
+                items.modules.push(m.ident.to_string())
+            }
+            Item::Use(u) if is_public(&u.vis) => reexports(&u.tree, "", &mut items.reexports),
+            _ => {}
+        }
+    }
+    Ok(items)
+}
+
+/// `sig` as a [`PublicFn`], if a test can call it with `Default::default()`
+/// for every argument.
+fn callable(sig: &syn::Signature) -> Option<PublicFn> {
+    if sig.generics.type_params().next().is_some()
+        || sig.asyncness.is_some()
+        || sig.unsafety.is_some()
+    {
+        return None;
+    }
+    let mut args = 0;
+    for input in &sig.inputs {
+        match input {
+            syn::FnArg::Typed(arg) if has_default(&arg.ty) => args += 1,
+            _ => return None,
+        }
+    }
+    let returns = match &sig.output {
+        syn::ReturnType::Default => Returns::Nothing,
+        syn::ReturnType::Type(_, ty) if is_comparable(ty) => Returns::Comparable,
+        syn::ReturnType::Type(..) => Returns::Other,
+    };
+    Some(PublicFn {
+        name: sig.ident.to_string(),
+        args,
+        returns,
+    })
+}
+
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+    "usize", "f32", "f64", "String",
+];
+
+/// Last segment of a path type and its type arguments.
+fn path_type(ty: &syn::Type) -> Option<(String, Vec<&syn::Type>)> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    let args = match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((last.ident.to_string(), args))
+}
+
+fn has_default(ty: &syn::Type) -> bool {
+    const CONTAINERS: &[&str] = &[
+        "Vec", "Option", "HashMap", "HashSet", "BTreeMap", "BTreeSet", "VecDeque", "PathBuf",
+    ];
+    match ty {
+        // &T 只有 &str 和 &[T] 有 Default
+        syn::Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+            syn::Type::Slice(_) => true,
+            elem => path_type(elem).is_some_and(|(name, _)| name == "str"),
+        },
+        syn::Type::Tuple(tuple) => tuple.elems.iter().all(has_default),
+        syn::Type::Paren(inner) => has_default(&inner.elem),
+        ty => path_type(ty).is_some_and(|(name, _)| {
+            PRIMITIVES.contains(&name.as_str()) || CONTAINERS.contains(&name.as_str())
+        }),
+    }
+}
+
+fn is_comparable(ty: &syn::Type) -> bool {
+    match path_type(ty) {
+        Some((name, args)) if name == "Vec" || name == "Option" => {
+            args.len() == 1 && is_comparable(args[0])
+        }
+        Some((name, args)) => args.is_empty() && PRIMITIVES.contains(&name.as_str()),
+        None => false,
+    }
+}
+
+/// Flattens a `pub use` tree into `(source path, exported name)` pairs.
+fn reexports(tree: &UseTree, prefix: &str, out: &mut Vec<(String, String)>) {
+    match tree {
+        UseTree::Path(p) => reexports(&p.tree, &format!("{prefix}{}::", p.ident), out),
+        UseTree::Name(n) => out.push((format!("{prefix}{}", n.ident), n.ident.to_string())),
+        UseTree::Rename(r) => out.push((format!("{prefix}{}", r.ident), r.rename.to_string())),
+        UseTree::Glob(_) => out.push((format!("{prefix}*"), "*".to_owned())),
+        UseTree::Group(g) => {
+            for tree in &g.items {
+                reexports(tree, prefix, out);
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_error(path: &Path, e: syn::Error) -> MvpError {
+    let start = e.span().start();
+    MvpError::Custom(format!(
+        "Failed to parse {}:{}:{}: {e}",
+        path.display(),
+        start.line,
+        start.column + 1
+    ))
+}
+
+/// Declarations found at the top level of a file, with their 1-based line
+/// ranges (attributes and doc comments included).
+struct Decl {
+    kind: DeclKind,
+    public: bool,
+    key: String,
+    start: usize,
+    end: usize,
+}
+
+#[derive(PartialEq, Eq)]
+enum DeclKind {
+    Mod,
+    Use,
+    Other,
+}
+
+fn declarations(text: &str) -> syn::Result<Vec<Decl>> {
+    let file = syn::parse_file(text)?;
+    Ok(file
+        .items
+        .iter()
+        .map(|item| {
+            let (kind, public, key) = match item {
+                Item::Mod(m) if m.content.is_none() => {
+                    (DeclKind::Mod, is_public(&m.vis), m.ident.to_string())
+                }
+                Item::Use(u) => (DeclKind::Use, is_public(&u.vis), use_path(&u.tree)),
+                _ => (DeclKind::Other, false, String::new()),
+            };
+            let span = item.span();
+            Decl {
+                kind,
+                public,
+                key,
+                start: span.start().line,
+                end: span.end().line,
+            }
+        })
+        .collect())
+}
+
+/// Plain `pub`: `pub(crate)` and `pub(super)` items are invisible to
+/// integration tests and other crates.
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// `a::b::{C, D}` without spaces, for sorting and duplicate checks.
+fn use_path(tree: &UseTree) -> String {
+    match tree {
+        UseTree::Path(p) => format!("{}::{}", p.ident, use_path(&p.tree)),
+        UseTree::Name(n) => n.ident.to_string(),
+        UseTree::Rename(r) => format!("{} as {}", r.ident, r.rename),
+        UseTree::Glob(_) => "*".to_owned(),
+        UseTree::Group(g) => format!(
+            "{{{}}}",
+            g.items.iter().map(use_path).collect::<Vec<_>>().join(",")
+        ),
+    }
+}
+
+/// Adds `mod name;` (or `pub mod name;`) to `text`, in sorted position among
+/// the existing declarations of the same visibility, or after the imports
+/// when there are none. Every other line is left untouched.
+///
+/// Returns `None` when the module is already declared, with `mod name;` or
+/// inline as `mod name { … }`.
+pub fn insert_mod(text: &str, name: &str, public: bool) -> syn::Result<Option<String>> {
+    let file = syn::parse_file(text)?;
+    if file
+        .items
+        .iter()
+        .any(|item| matches!(item, Item::Mod(m) if m.ident == name))
+    {
+        return Ok(None);
+    }
+    let decls = declarations(text)?;
+    let vis = if public { "pub " } else { "" };
+    let line = format!("{vis}mod {name};\n");
+    Ok(Some(insert_sorted(
+        text,
+        &decls,
+        DeclKind::Mod,
+        public,
+        name,
+        line,
+    )))
+}
+
+/// Adds `use item;` (or `pub use item;`) to `text`, in sorted position among
+/// the existing imports of the same visibility, or after the module
+/// declarations when there are none.
+///
+/// Returns `None` when the exact import already exists.
+pub fn insert_use(text: &str, item: &str, public: bool) -> syn::Result<Option<String>> {
+    let tree: UseTree = syn::parse_str(item)?;
+    let key = use_path(&tree);
+    let decls = declarations(text)?;
+    if decls
+        .iter()
+        .any(|d| d.kind == DeclKind::Use && d.key == key)
+    {
+        return Ok(None);
+    }
+    let vis = if public { "pub " } else { "" };
+    let line = format!("{vis}use {item};\n");
+    Ok(Some(insert_sorted(
+        text,
+        &decls,
+        DeclKind::Use,
+        public,
+        &key,
+        line,
+    )))
+}
+
+fn insert_sorted(
+    text: &str,
+    decls: &[Decl],
+    kind: DeclKind,
+    public: bool,
+    key: &str,
+    line: String,
+) -> String {
+    let mut lines: Vec<String> = text.split_inclusive('\n').map(str::to_owned).collect();
+    if let Some(last) = lines.last_mut().filter(|l| !l.ends_with('\n')) {
+        last.push('\n');
+    }
+
+    let same: Vec<_> = decls
+        .iter()
+        .filter(|d| d.kind == kind && d.public == public)
+        .collect();
+    // 同类声明里按名字找位置；没有同类时，mod 放在 use 之后，use 放在 mod 之后
+    let (index, separate) = if let Some(next) = same.iter().find(|d| d.key.as_str() > key) {
+        (next.start - 1, false)
+    } else if let Some(last) = same.last() {
+        (last.end, false)
+    } else if let Some(last) = decls.iter().rfind(|d| d.kind == kind) {
+        (last.end, true)
+    } else if let Some(last) = decls.iter().rfind(|d| d.kind != DeclKind::Other) {
+        (last.end, true)
+    } else if let Some(first) = decls.first() {
+        lines.insert(first.start - 1, "\n".to_owned());
+        (first.start - 1, false)
+    } else {
+        let separate = lines.iter().any(|l| !l.trim().is_empty());
+        (lines.len(), separate)
+    };
+
+    if separate {
+        lines.insert(index, line);
+        lines.insert(index, "\n".to_owned());
+    } else {
+        lines.insert(index, line);
+    }
+    lines.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mods_are_inserted_in_sorted_position() {
+        let text = "//! Crate docs\n\nmod alpha;\n/// Gamma docs\nmod gamma;\n\npub mod zeta;\n\nfn main() {}\n";
+        assert_eq!(
+            insert_mod(text, "beta", false).unwrap().unwrap(),
+            "//! Crate docs\n\nmod alpha;\nmod beta;\n/// Gamma docs\nmod gamma;\n\npub mod zeta;\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            insert_mod(text, "omega", true).unwrap().unwrap(),
+            "//! Crate docs\n\nmod alpha;\n/// Gamma docs\nmod gamma;\n\npub mod omega;\npub mod zeta;\n\nfn main() {}\n"
+        );
+        assert_eq!(insert_mod(text, "gamma", true).unwrap(), None);
+        // 内联模块同样占用这个名字
+        let inline =
+            "fn main() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn it_works() {}\n}\n";
+        assert_eq!(insert_mod(inline, "tests", false).unwrap(), None);
+    }
+
+    #[test]
+    fn first_declarations_go_after_imports() {
+        assert_eq!(
+            insert_mod("use std::fs;\n\nfn main() {}", "cli", false)
+                .unwrap()
+                .unwrap(),
+            "use std::fs;\n\nmod cli;\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            insert_mod("fn main() {}\n", "cli", false).unwrap().unwrap(),
+            "mod cli;\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            insert_mod("", "cli", true).unwrap().unwrap(),
+            "pub mod cli;\n"
+        );
+        assert_eq!(
+            insert_use("mod a;\nmod b;\n", "b::B", true)
+                .unwrap()
+                .unwrap(),
+            "mod a;\nmod b;\n\npub use b::B;\n"
+        );
+        assert_eq!(insert_use("pub use b::B;\n", "b :: B", true).unwrap(), None);
+    }
+}
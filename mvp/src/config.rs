@@ -0,0 +1,285 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tera::Context;
+
+use crate::{error::MvpError, i18n::Lang};
+
+/// Directory holding user configuration and plugins.
+///
+/// `MVP_CONFIG_DIR` wins, then `$XDG_CONFIG_HOME/mvp`, then `~/.config/mvp`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("MVP_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("mvp"))
+}
+
+/// Contents of `<config dir>/config.toml`.
+///
+/// ```toml
+/// lang = "zh"
+///
+/// [strategies.docs]
+/// description = "README plus licenses"
+/// files = { "README.md" = "README.md", "LICENSE-MIT" = "LICENSE-MIT" }
+///
+/// [toolchain]
+/// channel = "1.89"
+/// targets = ["wasm32-unknown-unknown"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Extra strategies made of existing templates, keyed by name.
+    #[serde(default)]
+    pub strategies: BTreeMap<String, StrategyConfig>,
+    /// `rust-toolchain.toml`
+    #[serde(default)]
+    pub toolchain: ToolchainConfig,
+    /// `clippy.toml`
+    #[serde(default)]
+    pub clippy: ClippyConfig,
+    /// `deny.toml`
+    #[serde(default)]
+    pub deny: DenyConfig,
+    /// `.cargo/config.toml`
+    #[serde(default)]
+    pub cargo: CargoConfig,
+    /// SPDX headers from `mvp add headers`
+    #[serde(default)]
+    pub headers: HeadersConfig,
+    /// Language of templates and messages; `--lang` overrides it.
+    #[serde(default)]
+    pub lang: Lang,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub description: Option<String>,
+    /// Destination path (relative to the target) mapped to a template name.
+    pub files: BTreeMap<PathBuf, String>,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+}
+
+/// Rhai scripts run around a configured strategy; see
+/// [`crate::add::hooks`].
+///
+/// ```toml
+/// [strategies.service.hooks]
+/// pre = "hooks/service-pre.rhai"
+/// post = "hooks/service-post.rhai"
+/// commands = ["cargo"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Runs before rendering; relative paths start at the config directory.
+    pub pre: Option<PathBuf>,
+    /// Runs after rendering, before anything is written.
+    pub post: Option<PathBuf>,
+    /// Programs hooks may `run`; none unless listed.
+    pub commands: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolchainConfig {
+    pub channel: String,
+    pub components: Vec<String>,
+    pub targets: Vec<String>,
+}
+
+impl Default for ToolchainConfig {
+    fn default() -> Self {
+        Self {
+            channel: "stable".to_owned(),
+            components: vec!["rustfmt".to_owned(), "clippy".to_owned()],
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// Lint thresholds, named as in `clippy.toml` with `_` for `-`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClippyConfig {
+    pub cognitive_complexity_threshold: u32,
+    pub too_many_arguments_threshold: u32,
+    pub too_many_lines_threshold: u32,
+    pub type_complexity_threshold: u32,
+}
+
+impl Default for ClippyConfig {
+    fn default() -> Self {
+        Self {
+            cognitive_complexity_threshold: 25,
+            too_many_arguments_threshold: 7,
+            too_many_lines_threshold: 100,
+            type_complexity_threshold: 250,
+        }
+    }
+}
+
+/// License and advisory policy for `cargo deny`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DenyConfig {
+    /// SPDX identifiers dependencies may be licensed under.
+    pub licenses: Vec<String>,
+    /// Advisory IDs to ignore, e.g. `RUSTSEC-2024-0436`.
+    pub ignore_advisories: Vec<String>,
+    /// `deny`, `warn` or `allow` for duplicate crate versions.
+    pub multiple_versions: String,
+}
+
+impl Default for DenyConfig {
+    fn default() -> Self {
+        Self {
+            licenses: ["MIT", "Apache-2.0", "Unicode-3.0"]
+                .map(str::to_owned)
+                .to_vec(),
+            ignore_advisories: Vec::new(),
+            multiple_versions: "warn".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CargoConfig {
+    /// `[alias]` entries, e.g. `lint = "clippy --all-targets -- -D warnings"`.
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Default for CargoConfig {
+    fn default() -> Self {
+        Self {
+            aliases: [
+                ("b", "build"),
+                ("c", "check"),
+                ("t", "test"),
+                ("lint", "clippy --all-targets -- -D warnings"),
+            ]
+            .into_iter()
+            .map(|(name, command)| (name.to_owned(), command.to_owned()))
+            .collect(),
+        }
+    }
+}
+
+/// Source file headers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HeadersConfig {
+    /// SPDX license expression.
+    pub license: String,
+    /// Copyright holder; empty means the `author` of the run.
+    pub holder: String,
+    /// Glob patterns, relative to the project, of files to leave alone.
+    pub exclude: Vec<String>,
+}
+
+impl Default for HeadersConfig {
+    fn default() -> Self {
+        Self {
+            license: "MIT OR Apache-2.0".to_owned(),
+            holder: String::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `config.toml` from [`config_dir`]; a missing file is an empty
+    /// config.
+    pub fn load() -> Result<Self, MvpError> {
+        match config_dir() {
+            Some(dir) => Self::load_from(&dir.join("config.toml")),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Exposes the policy sections to templates as `toolchain`, `clippy`,
+    /// `deny`, `cargo` and `headers`, keeping any value the caller already
+    /// set.
+    pub fn insert_into(&self, context: &mut Context) {
+        if !context.contains_key("toolchain") {
+            context.insert("toolchain", &self.toolchain);
+        }
+        if !context.contains_key("clippy") {
+            context.insert("clippy", &self.clippy);
+        }
+        if !context.contains_key("deny") {
+            context.insert("deny", &self.deny);
+        }
+        if !context.contains_key("cargo") {
+            context.insert("cargo", &self.cargo);
+        }
+        if !context.contains_key("headers") {
+            context.insert("headers", &self.headers);
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, MvpError> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let mut config: Self =
+                    toml::from_str(&text).map_err(|e| MvpError::ConfigInvalid {
+                        message: format!("{}: {e}", path.display()),
+                    })?;
+                // hook 脚本的相对路径以配置文件所在目录为准
+                let dir = path.parent().unwrap_or(Path::new(""));
+                for strategy in config.strategies.values_mut() {
+                    let hooks = &mut strategy.hooks;
+                    for script in [&mut hooks.pre, &mut hooks.post].into_iter().flatten() {
+                        *script = dir.join(&*script);
+                    }
+                }
+                Ok(config)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn missing_config_is_empty_and_bad_config_is_reported() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(Config::load_from(&path).unwrap().strategies.is_empty());
+
+        fs::write(
+            &path,
+            "[strategies.docs]\nfiles = { \"README.md\" = \"README.md\" }\n",
+        )
+        .unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(
+            config.strategies["docs"].files[Path::new("README.md")],
+            "README.md"
+        );
+
+        fs::write(&path, "unknown = 1\n").unwrap();
+        assert!(matches!(
+            Config::load_from(&path),
+            Err(MvpError::ConfigInvalid { .. })
+        ));
+    }
+}
@@ -1,7 +1,48 @@
+use std::{error::Error as _, fmt::Write, path::PathBuf};
+
+use serde_json::{Value, json};
 use thiserror::Error;
 
+use crate::templates::format::Format;
+
 #[derive(Error, Debug)]
 pub enum MvpError {
+    #[error("No strategy found for '{name}'")]
+    StrategyNotFound { name: String },
+    #[error("Invalid arguments for '{strategy}': {message}")]
+    InvalidArgs { strategy: String, message: String },
+    #[error("Failed to render template '{template}': {message}")]
+    TemplateRender {
+        template: String,
+        line: Option<usize>,
+        col: Option<usize>,
+        /// The source line at `line`, quoted in [`MvpError::report`].
+        source_line: Option<String>,
+        message: String,
+    },
+    #[error("{} would not be valid {format}: {message}", path.display())]
+    InvalidOutput {
+        path: PathBuf,
+        format: Format,
+        message: String,
+    },
+    #[error("Failed to write {}", path.display())]
+    WriteFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{} already exists", path.display())]
+    FileExists { path: PathBuf },
+    #[error("Failed to create project '{name}': {reason}")]
+    ProjectCreation { name: String, reason: String },
+    #[error("`{command}` failed with {status}")]
+    CommandFailed { command: String, status: String },
+    #[error("{message}")]
+    CheckFailed { message: String },
+    #[error("Hook {} failed: {message}", path.display())]
+    HookFailed { path: PathBuf, message: String },
+    #[error("Invalid configuration: {message}")]
+    ConfigInvalid { message: String },
     #[error("Tera Error: {0}")]
     Tera(#[from] tera::Error),
     #[error("IO Error: {0}")]
@@ -9,3 +50,279 @@ pub enum MvpError {
     #[error("Custom Error: {0}")]
     Custom(String),
 }
+
+impl MvpError {
+    /// Wraps a Tera render failure, locating the offending expression in the
+    /// template source when `source` knows it. `source` must give the text
+    /// the failing Tera instance was built from, e.g.
+    /// [`crate::templates::source_in`].
+    pub fn template_render<'s>(
+        template: &str,
+        err: tera::Error,
+        source: impl Fn(&str) -> Option<&'s str>,
+    ) -> Self {
+        // 最内层的错误信息最具体，比如 "Variable `nme` not found in context ..."
+        let mut message = err.to_string();
+        let mut current = err.source();
+        while let Some(e) = current {
+            message = e.to_string();
+            current = e.source();
+        }
+        // 出错的可能是 include 进来的模板，以信息里的 'name' 为准
+        let template = rendering_template(&message).unwrap_or(template).to_owned();
+        let located = source(&template).and_then(|source| {
+            let (line, col) = locate(source, &message)?;
+            Some((line, col, source.lines().nth(line - 1)?.to_owned()))
+        });
+        let (line, col, source_line) = match located {
+            Some((line, col, source_line)) => (Some(line), Some(col), Some(source_line)),
+            None => (None, None, None),
+        };
+        MvpError::TemplateRender {
+            template,
+            line,
+            col,
+            source_line,
+            message,
+        }
+    }
+
+    /// Stable identifier for the error class, used in JSON output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MvpError::StrategyNotFound { .. } => "strategy_not_found",
+            MvpError::InvalidArgs { .. } => "invalid_args",
+            MvpError::TemplateRender { .. } => "template_render",
+            MvpError::InvalidOutput { .. } => "invalid_output",
+            MvpError::WriteFailed { .. } => "write_failed",
+            MvpError::FileExists { .. } => "file_exists",
+            MvpError::ProjectCreation { .. } => "project_creation",
+            MvpError::CommandFailed { .. } => "command_failed",
+            MvpError::CheckFailed { .. } => "check_failed",
+            MvpError::HookFailed { .. } => "hook_failed",
+            MvpError::ConfigInvalid { .. } => "config_invalid",
+            MvpError::Tera(_) => "tera",
+            MvpError::Io(_) => "io",
+            MvpError::Custom(_) => "custom",
+        }
+    }
+
+    /// Process exit code for this class of error, following `sysexits.h`.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            MvpError::StrategyNotFound { .. } | MvpError::InvalidArgs { .. } => 64,
+            MvpError::TemplateRender { .. }
+            | MvpError::InvalidOutput { .. }
+            | MvpError::Tera(_) => 65,
+            MvpError::CommandFailed { .. } | MvpError::HookFailed { .. } => 70,
+            MvpError::ProjectCreation { .. } => 71,
+            MvpError::WriteFailed { .. } | MvpError::FileExists { .. } => 73,
+            MvpError::Io(_) => 74,
+            MvpError::ConfigInvalid { .. } => 78,
+            MvpError::CheckFailed { .. } | MvpError::Custom(_) => 1,
+        }
+    }
+
+    /// Human-readable report; template errors show the offending line with a
+    /// caret under the failing expression.
+    pub fn report(&self) -> String {
+        let mut out = format!("error: {self}\n");
+        if let MvpError::TemplateRender {
+            template,
+            line: Some(line),
+            col: Some(col),
+            source_line: Some(source_line),
+            ..
+        } = self
+        {
+            let gutter = " ".repeat(line.to_string().len());
+            let _ = writeln!(out, "{gutter}--> {template}:{line}:{col}");
+            let _ = writeln!(out, "{gutter} |");
+            let _ = writeln!(out, "{line} | {source_line}");
+            let _ = writeln!(out, "{gutter} | {}^", " ".repeat(col - 1));
+        }
+        let mut current = self.source();
+        while let Some(e) = current {
+            // #[from] 变体的 source 就是自己的内容，不重复打印
+            let cause = e.to_string();
+            if !out.contains(&cause) {
+                let _ = writeln!(out, "caused by: {cause}");
+            }
+            current = e.source();
+        }
+        out
+    }
+
+    /// Machine-readable form for `--json`.
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "kind": self.kind(),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+        });
+        match self {
+            MvpError::StrategyNotFound { name } => error["name"] = json!(name),
+            MvpError::InvalidArgs { strategy, .. } => error["name"] = json!(strategy),
+            MvpError::TemplateRender {
+                template,
+                line,
+                col,
+                ..
+            } => {
+                error["template"] = json!(template);
+                error["line"] = json!(line);
+                error["col"] = json!(col);
+            }
+            MvpError::InvalidOutput { path, format, .. } => {
+                error["path"] = json!(path);
+                error["format"] = json!(format.to_string());
+            }
+            MvpError::WriteFailed { path, .. }
+            | MvpError::FileExists { path }
+            | MvpError::HookFailed { path, .. } => error["path"] = json!(path),
+            MvpError::ProjectCreation { name, .. } => error["project"] = json!(name),
+            MvpError::CommandFailed { command, .. } => error["command"] = json!(command),
+            _ => {}
+        }
+        let mut causes = Vec::new();
+        let mut current = self.source();
+        while let Some(e) = current {
+            causes.push(e.to_string());
+            current = e.source();
+        }
+        error["causes"] = json!(causes);
+        json!({ "error": error })
+    }
+}
+
+/// The template named in "... while rendering 'name'".
+fn rendering_template(message: &str) -> Option<&str> {
+    let rest = message.split("while rendering '").nth(1)?;
+    rest.split('\'').next()
+}
+
+/// Finds the first backticked name from `message` inside a `{{ }}` or
+/// `{% %}` tag of `source`, as a 1-based line and column.
+fn locate(source: &str, message: &str) -> Option<(usize, usize)> {
+    let needle = message.split('`').nth(1)?;
+    let needle = needle.split(['.', '[']).next().filter(|n| !n.is_empty())?;
+
+    let mut offset = 0;
+    while let Some(start) = source[offset..].find('{').map(|i| i + offset) {
+        let rest = &source[start..];
+        let close = if rest.starts_with("{{") {
+            "}}"
+        } else if rest.starts_with("{%") {
+            "%}"
+        } else {
+            offset = start + 1;
+            continue;
+        };
+        let end = rest.find(close).map_or(source.len(), |i| start + i);
+        let tag = &source[start..end];
+        let mut search = 0;
+        while let Some(found) = tag[search..].find(needle).map(|i| i + search) {
+            let before = tag[..found].chars().next_back();
+            let after = tag[found + needle.len()..].chars().next();
+            let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            if !is_word(before) && !is_word(after) {
+                let absolute = start + found;
+                let line = source[..absolute].matches('\n').count() + 1;
+                let line_start = source[..absolute].rfind('\n').map_or(0, |i| i + 1);
+                let col = source[line_start..absolute].chars().count() + 1;
+                return Some((line, col));
+            }
+            search = found + needle.len();
+        }
+        offset = end.max(start + 1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates;
+
+    #[test]
+    fn locates_the_failing_variable() {
+        let source = "# Title\n\nHello {{ author }}, {{ nme | upper }}\n";
+        let message = "Variable `nme` not found in context while rendering 'README.md'";
+        assert_eq!(locate(source, message), Some((3, 24)));
+        assert_eq!(rendering_template(message), Some("README.md"));
+    }
+
+    #[test]
+    fn ignores_matches_outside_tags_and_inside_longer_names() {
+        let source = "name {{ names }}\n{% if name %}x{% endif %}";
+        assert_eq!(locate(source, "Variable `name` not found"), Some((2, 7)));
+    }
+
+    #[test]
+    fn render_errors_point_at_the_template_line() {
+        let tera = templates::embedded().unwrap();
+        let err = tera
+            .render("LICENSE-MIT", &tera::Context::new())
+            .unwrap_err();
+        let err = MvpError::template_render("LICENSE-MIT", err, templates::source);
+
+        assert!(matches!(
+            &err,
+            MvpError::TemplateRender { template, line: Some(3), col: Some(18), .. }
+                if template == "LICENSE-MIT"
+        ));
+        let report = err.report();
+        assert!(report.contains("--> LICENSE-MIT:3:18"), "{report}");
+        assert!(
+            report.contains("3 | Copyright (c) {{ year }} {{ author }}\n"),
+            "{report}"
+        );
+        assert!(report.contains("  |                  ^\n"), "{report}");
+    }
+
+    #[test]
+    fn translated_templates_are_quoted_from_the_translation() {
+        let tera = templates::localized(crate::i18n::Lang::Zh);
+        let zh = templates::source("zh/README.md").unwrap();
+        assert_eq!(templates::source_in(tera, "README.md"), Some(zh));
+        assert_ne!(templates::source("README.md"), Some(zh));
+
+        let err = templates::render(tera, "README.md", &tera::Context::new()).unwrap_err();
+        let err =
+            MvpError::template_render("README.md", err, |name| templates::source_in(tera, name));
+        let MvpError::TemplateRender {
+            line: Some(line),
+            source_line: Some(source_line),
+            ..
+        } = &err
+        else {
+            panic!("{err:?}");
+        };
+        assert_eq!(zh.lines().nth(line - 1), Some(source_line.as_str()));
+    }
+
+    #[test]
+    fn error_classes_have_distinct_exit_codes() {
+        let errors = [
+            MvpError::StrategyNotFound { name: "x".into() },
+            MvpError::template_render("t", tera::Error::msg("boom"), templates::source),
+            MvpError::FileExists { path: "a".into() },
+            MvpError::ProjectCreation {
+                name: "p".into(),
+                reason: "r".into(),
+            },
+            MvpError::ConfigInvalid {
+                message: "m".into(),
+            },
+            MvpError::CommandFailed {
+                command: "cargo new".into(),
+                status: "exit status: 101".into(),
+            },
+            MvpError::Io(std::io::Error::other("io")),
+            MvpError::Custom("c".into()),
+        ];
+        let codes: std::collections::BTreeSet<_> = errors.iter().map(MvpError::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(errors[0].to_json()["error"]["kind"], "strategy_not_found");
+    }
+}
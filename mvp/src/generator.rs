@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+
+use chrono::Datelike;
+use tera::{Context, Tera};
+
+use crate::{
+    add::{
+        base::AddStrategy,
+        context::{AddStrategyFactory, ResolvedStrategy},
+    },
+    config::Config,
+    error::MvpError,
+    i18n::Lang,
+    plan::Plan,
+    project::Project,
+    report, templates,
+};
+
+/// Entry point for embedding `mvp` in other tools.
+///
+/// ```
+/// use mvp::{Generator, plan::FileOp};
+///
+/// let plan = Generator::builder()
+///     .target("my-crate")
+/// #   .config(mvp::config::Config::default())
+///     .strategies(["fmt", "gi"])
+///     .plan()?;
+///
+/// // Nothing has been written yet; inspect the plan, then `plan.apply(..)`.
+/// assert!(matches!(&plan.ops()[0], FileOp::Write { path, .. } if path.ends_with("rustfmt.toml")));
+/// # Ok::<(), mvp::error::MvpError>(())
+/// ```
+pub struct Generator;
+
+impl Generator {
+    pub fn builder() -> GeneratorBuilder {
+        GeneratorBuilder::default()
+    }
+
+    /// The context every run starts from: the current year and the default
+    /// author.
+    pub fn base_context() -> Context {
+        let mut context = Context::new();
+        context.insert("year", &chrono::Utc::now().year());
+        context.insert("author", "Levio-Z");
+        context
+    }
+}
+
+/// Collects templates, context, target directory and strategies, then builds a
+/// [`Plan`] without touching the disk.
+pub struct GeneratorBuilder {
+    templates: Option<Tera>,
+    context: Option<Context>,
+    target: PathBuf,
+    strategies: Vec<String>,
+    custom: Vec<Box<dyn AddStrategy>>,
+    args: Vec<String>,
+    config: Option<Config>,
+    lang: Option<Lang>,
+}
+
+impl Default for GeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            templates: None,
+            context: None,
+            target: PathBuf::from("."),
+            strategies: Vec::new(),
+            custom: Vec::new(),
+            args: Vec::new(),
+            config: None,
+            lang: None,
+        }
+    }
+}
+
+impl GeneratorBuilder {
+    /// Templates to render with; defaults to the embedded templates in the
+    /// chosen [`lang`](Self::lang).
+    pub fn templates(mut self, tera: Tera) -> Self {
+        self.templates = Some(tera);
+        self
+    }
+
+    /// Context to render with; defaults to [`Generator::base_context`].
+    pub fn context(mut self, context: Context) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Directory the plan is rooted at; defaults to the current directory.
+    pub fn target(mut self, target: impl Into<PathBuf>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Strategies to run, in order, by name.
+    pub fn strategies<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.strategies.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Makes a strategy of your own available by its name, taking precedence
+    /// over built-ins and plugins.
+    pub fn register(mut self, strategy: Box<dyn AddStrategy>) -> Self {
+        self.custom.push(strategy);
+        self
+    }
+
+    /// Extra arguments handed to each strategy, as `mvp add <name> <args>..`
+    /// would.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// User configuration; defaults to [`Config::load`].
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Language of the embedded templates; defaults to `lang` in the config.
+    pub fn lang(mut self, lang: Lang) -> Self {
+        self.lang = Some(lang);
+        self
+    }
+
+    /// Runs every strategy against the target and returns what they would
+    /// write. Unless the context already has one, templates see the target
+    /// as `project` (see [`Project`]).
+    pub fn plan(self) -> Result<Plan, MvpError> {
+        let mut context = self.context.unwrap_or_else(Generator::base_context);
+        if !self.args.is_empty() {
+            context.insert("args", &self.args);
+        }
+
+        let config = match self.config {
+            Some(config) => config,
+            None => Config::load()?,
+        };
+        let tera = match &self.templates {
+            Some(tera) => tera,
+            None => templates::localized(self.lang.unwrap_or(config.lang)),
+        };
+        config.insert_into(&mut context);
+        if !context.contains_key("project") {
+            context.insert("project", &Project::inspect(&self.target));
+        }
+        let factory = AddStrategyFactory::get_add_strategy_factory();
+        let mut plan = Plan::new(self.target);
+        for name in &self.strategies {
+            if let Some(custom) = self.custom.iter().find(|s| s.name() == name) {
+                report::debug(format!("Using custom strategy '{name}'"));
+                custom.handle(tera, &mut context, &mut plan)?;
+                continue;
+            }
+            let strategy = factory
+                .resolve(name, &config)
+                .ok_or_else(|| MvpError::StrategyNotFound { name: name.clone() })?;
+            match &strategy {
+                ResolvedStrategy::Builtin(_) => {
+                    report::debug(format!("Using built-in strategy '{name}'"))
+                }
+                ResolvedStrategy::Config(_) => {
+                    report::debug(format!("Using strategy '{name}' from config.toml"))
+                }
+                ResolvedStrategy::Plugin(plugin) => report::debug(format!(
+                    "Using plugin {} for '{name}'",
+                    plugin.path().display()
+                )),
+            }
+            strategy.handle(tera, &mut context, &mut plan)?;
+        }
+        for op in plan.ops() {
+            report::planned(op);
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::FileOp;
+
+    struct Hello;
+
+    impl AddStrategy for Hello {
+        fn handle(&self, _: &Tera, context: &mut Context, plan: &mut Plan) -> Result<(), MvpError> {
+            let author = context.get("author").and_then(|v| v.as_str()).unwrap();
+            plan.write("hello.txt", format!("hello {author}"));
+            Ok(())
+        }
+        fn name(&self) -> &str {
+            "hello"
+        }
+    }
+
+    #[test]
+    fn plans_custom_and_builtin_strategies_under_target() {
+        let plan = Generator::builder()
+            .target("demo")
+            .config(Config::default())
+            .register(Box::new(Hello))
+            .strategies(["hello", "gi"])
+            .plan()
+            .unwrap();
+
+        assert_eq!(plan.root(), std::path::Path::new("demo"));
+        assert!(matches!(
+            &plan.ops()[0],
+            FileOp::Write { path, contents } if path.ends_with("hello.txt") && contents == "hello Levio-Z"
+        ));
+        assert_eq!(plan.ops()[1].path(), std::path::Path::new(".gitignore"));
+    }
+
+    #[test]
+    fn config_strategies_render_existing_templates() {
+        let config: Config =
+            toml::from_str("[strategies.docs]\nfiles = { \"docs/LICENSE\" = \"LICENSE-MIT\" }\n")
+                .unwrap();
+        let plan = Generator::builder()
+            .config(config)
+            .strategies(["docs"])
+            .plan()
+            .unwrap();
+
+        assert!(matches!(
+            &plan.ops()[0],
+            FileOp::Write { path, contents } if path.ends_with("docs/LICENSE") && contents.contains("Levio-Z")
+        ));
+    }
+
+    #[test]
+    fn unknown_strategy_is_an_error() {
+        let result = Generator::builder()
+            .config(Config::default())
+            .strategies(["no-such-strategy"])
+            .plan();
+        assert!(result.is_err());
+    }
+}
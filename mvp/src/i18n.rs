@@ -0,0 +1,231 @@
+//! English and Chinese output.
+//!
+//! Templates are localized by directory: `zh/README.md` replaces `README.md`
+//! when rendering in Chinese, and templates without a translation fall back
+//! to the default one (see [`crate::templates::localized`]). Progress
+//! messages are localized through [`Message`], using the process-wide
+//! language installed with [`set_lang`].
+
+use std::{fmt, path::Path, sync::RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::plan::Status;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// English
+    #[default]
+    En,
+    /// 简体中文
+    Zh,
+}
+
+impl Lang {
+    /// Directory under `templates/` holding this language's translations, or
+    /// `None` for the default templates.
+    pub fn template_dir(self) -> Option<&'static str> {
+        match self {
+            Lang::En => None,
+            Lang::Zh => Some("zh"),
+        }
+    }
+}
+
+static LANG: RwLock<Lang> = RwLock::new(Lang::En);
+
+/// Installs the process-wide language for messages.
+pub fn set_lang(lang: Lang) {
+    *LANG.write().unwrap_or_else(|e| e.into_inner()) = lang;
+}
+
+pub fn lang() -> Lang {
+    *LANG.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A progress message, printed in the current [`lang`].
+#[derive(Debug, Clone, Copy)]
+pub enum Message<'a> {
+    Add(&'a str),
+    CreatingProject(&'a str),
+    CreatingArchetype {
+        archetype: &'a str,
+        name: &'a str,
+    },
+    AddingInitFiles,
+    RunningStrategy(&'a str),
+    CreatingModule(&'a Path),
+    AddingDependency {
+        name: &'a str,
+        manifest: &'a str,
+    },
+    Unchanged(&'a str),
+    /// A file that exists without any `<!-- mvp:begin NAME -->` region.
+    NoRegions(&'a str),
+    /// A file `mvp add headers --check` would change; `stale` when it has an
+    /// outdated header rather than none.
+    HeaderOutdated {
+        path: &'a Path,
+        stale: bool,
+    },
+    Releasing {
+        package: &'a str,
+        from: &'a str,
+        to: &'a str,
+    },
+    /// Planning starts in a workspace member.
+    Member(&'a str),
+    MemberDone {
+        package: &'a str,
+        changed: usize,
+        unchanged: usize,
+    },
+    WroteManPages(&'a Path),
+    TemplatesFine(usize),
+    /// The text form of an applied operation, e.g. `Created src/lib.rs`.
+    Applied {
+        status: Status,
+        target: &'a str,
+        dry_run: bool,
+    },
+    Planned {
+        op: &'a str,
+        target: &'a str,
+    },
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text(lang()))
+    }
+}
+
+impl Message<'_> {
+    pub fn text(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => self.en(),
+            Lang::Zh => self.zh(),
+        }
+    }
+
+    fn en(&self) -> String {
+        match *self {
+            Message::Add(name) => format!("Add {name}"),
+            Message::CreatingProject(name) => format!("Creating project: {name}"),
+            Message::CreatingArchetype { archetype, name } => {
+                format!("Creating {archetype} project: {name}")
+            }
+            Message::AddingInitFiles => "Adding init files...".to_owned(),
+            Message::RunningStrategy(name) => format!("Running strategy: {name}"),
+            Message::CreatingModule(path) => format!("Creating module {}", path.display()),
+            Message::AddingDependency { name, manifest } => {
+                format!("Adding {name} to {manifest}")
+            }
+            Message::Unchanged(path) => format!("Leaving {path} unchanged"),
+            Message::NoRegions(path) => format!(
+                "Leaving {path} unchanged: it has no `<!-- mvp:begin NAME -->` regions to refresh"
+            ),
+            Message::HeaderOutdated { path, stale } => {
+                let status = if stale { "stale" } else { "missing" };
+                format!("{status}: {}", path.display())
+            }
+            Message::Releasing { package, from, to } => {
+                format!("Releasing {package} {from} -> {to}")
+            }
+            Message::Member(package) => format!("In {package}"),
+            Message::MemberDone {
+                package,
+                changed,
+                unchanged,
+            } => format!("{package}: {changed} changed, {unchanged} unchanged"),
+            Message::WroteManPages(dir) => format!("Wrote man pages to {}", dir.display()),
+            Message::TemplatesFine(count) => format!("{count} template(s) look fine"),
+            Message::Applied {
+                status,
+                target,
+                dry_run,
+            } => {
+                let prefix = if dry_run { "[dry-run] " } else { "" };
+                format!("{prefix}{status} {target}")
+            }
+            Message::Planned { op, target } => format!("Planned {op} {target}"),
+        }
+    }
+
+    fn zh(&self) -> String {
+        match *self {
+            Message::Add(name) => format!("添加 {name}"),
+            Message::CreatingProject(name) => format!("创建项目：{name}"),
+            Message::CreatingArchetype { archetype, name } => {
+                format!("创建 {archetype} 项目：{name}")
+            }
+            Message::AddingInitFiles => "添加初始化文件...".to_owned(),
+            Message::RunningStrategy(name) => format!("执行策略：{name}"),
+            Message::CreatingModule(path) => format!("创建模块 {}", path.display()),
+            Message::AddingDependency { name, manifest } => {
+                format!("向 {manifest} 添加 {name}")
+            }
+            Message::Unchanged(path) => format!("{path} 保持不变"),
+            Message::NoRegions(path) => {
+                format!("{path} 保持不变：其中没有可刷新的 `<!-- mvp:begin NAME -->` 区域")
+            }
+            Message::HeaderOutdated { path, stale } => {
+                let status = if stale { "过期" } else { "缺失" };
+                format!("{status}：{}", path.display())
+            }
+            Message::Releasing { package, from, to } => {
+                format!("发布 {package} {from} -> {to}")
+            }
+            Message::Member(package) => format!("进入 {package}"),
+            Message::MemberDone {
+                package,
+                changed,
+                unchanged,
+            } => format!("{package}：{changed} 个改动，{unchanged} 个未变"),
+            Message::WroteManPages(dir) => format!("man 手册已写入 {}", dir.display()),
+            Message::TemplatesFine(count) => format!("{count} 个模板没有问题"),
+            Message::Applied {
+                status,
+                target,
+                dry_run,
+            } => {
+                let prefix = if dry_run { "[预演] " } else { "" };
+                let status = match status {
+                    Status::Created => "已创建",
+                    Status::Overwritten => "已覆盖",
+                    Status::Unchanged => "未改动",
+                    Status::Skipped => "已跳过",
+                    Status::Appended => "已追加到",
+                    Status::Ran => "已执行",
+                };
+                format!("{prefix}{status} {target}")
+            }
+            Message::Planned { op, target } => {
+                let op = match op {
+                    "write" => "写入",
+                    "append" => "追加",
+                    "run" => "执行",
+                    other => other,
+                };
+                format!("计划{op} {target}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_follow_the_language() {
+        let message = Message::Applied {
+            status: Status::Created,
+            target: "README.md",
+            dry_run: true,
+        };
+        assert_eq!(message.text(Lang::En), "[dry-run] Created README.md");
+        assert_eq!(message.text(Lang::Zh), "[预演] 已创建 README.md");
+    }
+}
@@ -1,3 +1,17 @@
 pub mod add;
+pub mod config;
 pub mod error;
+pub mod generator;
+pub mod i18n;
+pub mod plan;
+pub mod project;
+pub mod release;
+pub mod report;
 pub mod templates;
+pub mod workspace;
+
+pub use generator::{Generator, GeneratorBuilder};
+pub use plan::Plan;
+
+#[cfg(test)]
+mod snapshots;
@@ -1,31 +1,28 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
 
-use chrono::Datelike;
-use clap::{Parser, Subcommand};
-use mvp::{add::context::AddStrategyFactory, error::MvpError};
-use tera::{Context, Result as TeraResult, Tera, Value};
-
-// Custom filter: does nothing
-fn do_nothing_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
-    Ok(value.clone())
-}
-// include! 会在编译期把文件内容插入这里
-include!("templates.rs");
-
-// Global template singleton
-pub static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| {
-    let mut tera = Tera::default();
-
-    // 循环注册 build.rs 生成的模板
-    for (name, content) in TEMPLATE_MAP {
-        tera.add_raw_template(name, content).unwrap();
-    }
-
-    // 可选配置
-    tera.autoescape_on(vec![".html", ".sql"]);
-    tera.register_filter("do_nothing", do_nothing_filter);
-    tera
-});
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{
+    CompleteEnv,
+    engine::{ArgValueCandidates, CompletionCandidate},
+    env::Shells,
+};
+use mvp::{
+    Generator,
+    add::{archetype::Archetype, context::AddStrategyFactory},
+    config::Config,
+    error::MvpError,
+    i18n::{self, Lang, Message},
+    plan::{ApplyOptions, Conflict},
+    project,
+    release::{self, Bump, ReleaseOptions},
+    report::{self, OutputFormat, Reporter, Verbosity},
+    templates::{self, TEMPLATES, authoring},
+    workspace,
+};
 
 #[derive(Parser)]
 #[command(
@@ -38,61 +35,321 @@ struct Cli {
     command: Option<Commands>,
     /// Optional list of values
     values: Option<Vec<String>>,
+    /// Show what would be written without touching the disk
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// What to do when a generated file already exists
+    #[arg(long, value_enum, default_value_t = Conflict::Overwrite, global = true)]
+    on_conflict: Conflict,
+    /// How to print progress, file operations and errors
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    output: OutputFormat,
+    /// Shorthand for `--output json`
+    #[arg(long, global = true)]
+    json: bool,
+    /// Only print errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print planned operations (-v) and internal details (-vv)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Language of generated files and messages; defaults to `lang` in
+    /// config.toml
+    #[arg(long, value_enum, global = true)]
+    lang: Option<Lang>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new component
     Add {
-        /// Name of the component to add
+        /// Name of the component to add, or of an `mvp-strategy-<name>` plugin
+        #[arg(add = ArgValueCandidates::new(strategy_candidates))]
         name: String,
+        /// Run in every member of the surrounding workspace
+        #[arg(long, conflicts_with = "package")]
+        workspace: bool,
+        /// Run in this workspace member; repeat for several
+        #[arg(short, long, value_name = "NAME")]
+        package: Vec<String>,
+        /// Extra arguments passed through to the strategy
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Create a new project
+    Init {
+        /// Name of the project directory and package
+        name: String,
+        /// Generate real sources from an archetype instead of `cargo new`
+        #[arg(long, value_enum)]
+        archetype: Option<Archetype>,
+        /// Skip `git init` and the editor/config files
+        #[arg(long)]
+        no_vcs: bool,
+    },
+    /// Bump the version, update CHANGELOG.md from conventional commits and tag
+    ///
+    /// Works on the package in the current directory and only uses the local
+    /// git repository.
+    Release {
+        #[arg(value_enum)]
+        bump: Bump,
+        /// Only edit the files; don't commit or tag
+        #[arg(long)]
+        no_tag: bool,
+    },
+    /// Try out templates without rebuilding mvp
+    Templates {
+        #[command(subcommand)]
+        command: TemplatesCommand,
+    },
+    /// Print the shell completion script
+    ///
+    /// e.g. `source <(mvp completions bash)`; completing `mvp add <TAB>`
+    /// lists built-in, configured and plugin strategies.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Generate roff man pages
+    Man {
+        /// Write `mvp.1` and one page per subcommand here instead of printing
+        /// `mvp.1` to stdout
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
     },
 }
 
-fn fill_context_with_year_and_author(context: &mut Context) {
-    let current_year = chrono::Utc::now().year();
-    context.insert("year", &current_year);
-    context.insert("author", "Levio-Z");
+#[derive(Subcommand)]
+enum TemplatesCommand {
+    /// Print a template rendered with its default context
+    Render {
+        /// Template name, e.g. `README.md` or a path below `--dir`
+        name: String,
+        /// TOML file whose values override the default context
+        #[arg(long)]
+        context: Option<PathBuf>,
+        /// Directory of templates layered over the embedded ones
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Flag undefined variables and blocks that are never rendered
+    Lint {
+        /// Directory of templates to lint; defaults to the embedded ones
+        dir: Option<PathBuf>,
+        /// TOML file whose values count as defined
+        #[arg(long)]
+        context: Option<PathBuf>,
+    },
+    /// Re-render a template directory into a preview directory on every change
+    Watch {
+        dir: PathBuf,
+        /// Where rendered files go
+        #[arg(long, default_value = "preview")]
+        out: PathBuf,
+        /// TOML file whose values override the default context
+        #[arg(long)]
+        context: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    Powershell,
 }
 
-fn main() -> Result<(), MvpError> {
+/// `mvp add <TAB>`：内置策略、config.toml 里声明的策略和已安装的插件
+fn strategy_candidates() -> Vec<CompletionCandidate> {
+    let config = Config::load().unwrap_or_default();
+    AddStrategyFactory::get_add_strategy_factory()
+        .available(&config)
+        .into_iter()
+        .map(|(name, help)| CompletionCandidate::new(name).help(Some(help.into())))
+        .collect()
+}
+
+fn main() -> ExitCode {
+    // 补全脚本以 COMPLETE=<shell> 回调本程序，处理完直接退出
+    CompleteEnv::with_factory(Cli::command).complete();
     let cli = Cli::parse();
-    run(&cli)
+    let format = if cli.json {
+        OutputFormat::Json
+    } else {
+        cli.output
+    };
+    report::set_reporter(Reporter::new(
+        format,
+        Verbosity::from_flags(cli.quiet, cli.verbose),
+    ));
+    // 配置文件有错时这里先用默认语言，错误留给后面真正加载配置的地方报告
+    i18n::set_lang(
+        cli.lang
+            .unwrap_or_else(|| Config::load().map(|c| c.lang).unwrap_or_default()),
+    );
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            report::error(&e);
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
 
 fn run(cli: &Cli) -> Result<(), MvpError> {
     for name in TEMPLATES.get_template_names() {
-        println!("Loaded template: {name}");
+        report::debug(format!("Loaded template: {name}"));
     }
-    let mut context = Context::new();
-    fill_context_with_year_and_author(&mut context);
-    match &cli.command {
-        Some(Commands::Add { name }) => {
-            if let Some(handler) = AddStrategyFactory::get_add_strategy_factory().get(name) {
-                println!("Add {}", name);
-                handler.handle(&TEMPLATES, &mut context)?;
-            } else {
-                eprintln!("No strategy found for '{}'.", name);
+    let options = ApplyOptions {
+        dry_run: cli.dry_run,
+        on_conflict: cli.on_conflict,
+    };
+    let mut context = Generator::base_context();
+    let builder = match &cli.command {
+        Some(Commands::Completions { shell }) => return print_completions(*shell),
+        Some(Commands::Man { out_dir }) => return print_man(out_dir.as_deref()),
+        Some(Commands::Templates { command }) => return run_templates(command),
+        Some(Commands::Release { bump, no_tag }) => {
+            let release = ReleaseOptions {
+                bump: *bump,
+                tag: !*no_tag,
+                dry_run: cli.dry_run,
+            };
+            let tera = templates::localized(i18n::lang());
+            // 跳过或拒绝改写清单会让 git 给没升版本的树打标签，发布总是覆盖
+            let options = ApplyOptions {
+                on_conflict: Conflict::Overwrite,
+                ..options
+            };
+            release::plan(&std::env::current_dir()?, tera, release)?.apply(&options)?;
+            return Ok(());
+        }
+        Some(Commands::Add {
+            name,
+            workspace,
+            package,
+            args,
+        }) if *workspace || !package.is_empty() => {
+            report::progress(Message::Add(name));
+            let root = project::workspace_root(&std::env::current_dir()?).ok_or_else(|| {
+                MvpError::InvalidArgs {
+                    strategy: name.clone(),
+                    message: "not inside a Cargo workspace".into(),
+                }
+            })?;
+            let members = workspace::select(workspace::members(&root)?, package)?;
+            let plan = workspace::plan(&root, &members, |_| {
+                Generator::builder()
+                    .context(context.clone())
+                    .lang(i18n::lang())
+                    .strategies([name])
+                    .args(args)
+            })?;
+            plan.apply(&options)?;
+            return Ok(());
+        }
+        Some(Commands::Add { name, args, .. }) => {
+            report::progress(Message::Add(name));
+            // 内置策略优先，找不到再回退到 PATH 上的 mvp-strategy-<name>
+            Generator::builder().strategies([name]).args(args)
+        }
+        Some(Commands::Init {
+            name,
+            archetype,
+            no_vcs,
+        }) => {
+            let mut values = vec![name.as_str()];
+            if *no_vcs {
+                values.push("n");
+            }
+            context.insert("init_values", &values);
+            if let Some(archetype) = archetype {
+                context.insert("archetype", archetype);
             }
+            Generator::builder().strategies(["init"])
         }
         None => {
             context.insert("init_values", &cli.values);
-            if let Some(handler) = AddStrategyFactory::get_add_strategy_factory().get("init") {
-                handler.handle(&TEMPLATES, &mut context)?;
-            } else {
-                eprintln!("No strategy found for 'init'.");
+            Generator::builder().strategies(["init"])
+        }
+    };
+    let plan = builder.context(context).lang(i18n::lang()).plan()?;
+    plan.apply(&options)?;
+    Ok(())
+}
+
+fn run_templates(command: &TemplatesCommand) -> Result<(), MvpError> {
+    match command {
+        TemplatesCommand::Render { name, context, dir } => {
+            let loaded = authoring::load(dir.as_deref())?;
+            let context = authoring::context(name, context.as_deref())?;
+            let mut out = io::stdout().lock();
+            out.write_all(authoring::render(&loaded, name, &context)?.as_bytes())?;
+            out.flush()?;
+        }
+        TemplatesCommand::Lint { dir, context } => {
+            let authoring::Loaded { tera, names, .. } = authoring::load(dir.as_deref())?;
+            let mut count = 0;
+            for name in &names {
+                for finding in authoring::lint(&tera, name, context.as_deref())? {
+                    report::progress(format!("{}: {}", finding.template, finding.message));
+                    count += 1;
+                }
+            }
+            if count > 0 {
+                return Err(MvpError::CheckFailed {
+                    message: format!("{count} problem(s) in {} template(s)", names.len()),
+                });
             }
+            report::progress(Message::TemplatesFine(names.len()));
+        }
+        TemplatesCommand::Watch { dir, out, context } => {
+            authoring::watch(dir, out, context.as_deref())?;
+        }
+    }
+    Ok(())
+}
+
+fn print_completions(shell: Shell) -> Result<(), MvpError> {
+    let name = match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+        Shell::Elvish => "elvish",
+        Shell::Powershell => "powershell",
+    };
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(name)
+        .ok_or_else(|| MvpError::Custom(format!("Unsupported shell: {name}")))?;
+    let bin = Cli::command().get_name().to_owned();
+    completer.write_registration("COMPLETE", &bin, &bin, &bin, &mut io::stdout())?;
+    Ok(())
+}
+
+fn print_man(out_dir: Option<&std::path::Path>) -> Result<(), MvpError> {
+    let cmd = Cli::command();
+    match out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            clap_mangen::generate_to(cmd, dir)?;
+            report::progress(Message::WroteManPages(dir));
+        }
+        None => {
+            let mut out = io::stdout().lock();
+            clap_mangen::Man::new(cmd).render(&mut out)?;
+            out.flush()?;
         }
     }
-    // 这里可以根据解析到的命令行参数执行相应的逻辑
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::time::Duration;
-
     use tempfile::tempdir;
 
     use super::*;
@@ -101,6 +358,17 @@ mod tests {
         use clap::CommandFactory;
         Cli::command().debug_assert();
     }
+    #[test]
+    fn add_completes_builtin_strategies() {
+        let names: Vec<_> = strategy_candidates()
+            .iter()
+            .map(|c| c.get_value().to_string_lossy().into_owned())
+            .collect();
+        for builtin in ["fmt", "gi", "init", "lic", "md", "vscode"] {
+            assert!(names.iter().any(|n| n == builtin), "{builtin} missing");
+        }
+    }
+
     // 如果 TEMPLATES 或全局资源在测试中会被修改，需要用 Mutex 或 LazyLock + reset
     // 方法保证环境还原
 
@@ -114,15 +382,10 @@ mod tests {
         let original_dir = std::env::current_dir().expect("Failed to get current dir");
 
         // 切换到临时目录
-        std::env::set_current_dir(&temp_path).expect("Failed to change dir");
+        std::env::set_current_dir(temp_path).expect("Failed to change dir");
 
         // 构造 CLI
-        let cli = Cli {
-            command: Some(Commands::Add {
-                name: "vscode".to_string(),
-            }),
-            values: None,
-        };
+        let cli = Cli::parse_from(["mvp", "add", "vscode"]);
 
         // 调用核心逻辑
         let result = run(&cli);
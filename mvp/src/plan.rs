@@ -0,0 +1,431 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::Write,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::MvpError, report, templates::format::Format};
+
+/// A single change a strategy wants to make in the target directory.
+///
+/// Paths are relative to the plan root; nothing touches the disk until the
+/// plan is applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FileOp {
+    /// Create `path` with `contents`, replacing it according to the conflict
+    /// policy if it already exists.
+    Write { path: PathBuf, contents: String },
+    /// Append `contents` to `path`, creating it if needed.
+    Append { path: PathBuf, contents: String },
+    /// Run a command in `dir`, e.g. `cargo new`.
+    Run {
+        program: String,
+        args: Vec<String>,
+        #[serde(default)]
+        dir: PathBuf,
+    },
+}
+
+impl FileOp {
+    /// The path this operation works on (the working directory for `Run`).
+    pub fn path(&self) -> &Path {
+        match self {
+            FileOp::Write { path, .. } | FileOp::Append { path, .. } => path,
+            FileOp::Run { dir, .. } => dir,
+        }
+    }
+
+    fn prefixed(self, prefix: &Path) -> Self {
+        match self {
+            FileOp::Write { path, contents } => FileOp::Write {
+                path: prefix.join(path),
+                contents,
+            },
+            FileOp::Append { path, contents } => FileOp::Append {
+                path: prefix.join(path),
+                contents,
+            },
+            FileOp::Run { program, args, dir } => FileOp::Run {
+                program,
+                args,
+                dir: prefix.join(dir),
+            },
+        }
+    }
+}
+
+/// What to do when a `Write` targets a file that already exists with
+/// different contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Conflict {
+    /// Replace the existing file (the historical behaviour).
+    #[default]
+    Overwrite,
+    /// Leave the existing file alone.
+    Skip,
+    /// Stop with an error.
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApplyOptions {
+    /// Report what would happen without touching the disk.
+    pub dry_run: bool,
+    pub on_conflict: Conflict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Created,
+    Overwritten,
+    Unchanged,
+    Skipped,
+    Appended,
+    Ran,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Created => "Created",
+            Status::Overwritten => "Overwrote",
+            Status::Unchanged => "Unchanged",
+            Status::Skipped => "Skipped",
+            Status::Appended => "Appended to",
+            Status::Ran => "Ran",
+        })
+    }
+}
+
+/// The result of applying (or dry-running) one [`FileOp`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Applied {
+    pub op: FileOp,
+    pub status: Status,
+}
+
+/// An ordered list of [`FileOp`]s rooted at a target directory.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Plan {
+    root: PathBuf,
+    ops: Vec<FileOp>,
+}
+
+impl Plan {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// The directory operations are relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn ops(&self) -> &[FileOp] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn push(&mut self, op: FileOp) {
+        self.ops.push(op);
+    }
+
+    pub fn write(&mut self, path: impl Into<PathBuf>, contents: impl Into<String>) {
+        self.push(FileOp::Write {
+            path: path.into(),
+            contents: contents.into(),
+        });
+    }
+
+    pub fn append(&mut self, path: impl Into<PathBuf>, contents: impl Into<String>) {
+        self.push(FileOp::Append {
+            path: path.into(),
+            contents: contents.into(),
+        });
+    }
+
+    pub fn run<I, S>(&mut self, program: &str, args: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.push(FileOp::Run {
+            program: program.to_owned(),
+            args: args.into_iter().map(Into::into).collect(),
+            dir: PathBuf::new(),
+        });
+    }
+
+    /// A child plan rooted at `dir` inside this one; merge it back with
+    /// [`Plan::nest`].
+    pub fn child(&self, dir: impl AsRef<Path>) -> Plan {
+        Plan::new(self.root.join(dir))
+    }
+
+    /// Appends the operations of `child`, re-rooted under `dir`.
+    pub fn nest(&mut self, dir: impl AsRef<Path>, child: Plan) {
+        let dir = dir.as_ref();
+        self.ops
+            .extend(child.ops.into_iter().map(|op| op.prefixed(dir)));
+    }
+
+    /// Executes the plan in order against the filesystem. JSON, TOML and
+    /// YAML files are parsed first, and paths and conflicts checked, so
+    /// nothing is written when one of them would stop the plan halfway.
+    pub fn apply(&self, options: &ApplyOptions) -> Result<Vec<Applied>, MvpError> {
+        self.validate()?;
+        self.preflight(options)?;
+        let mut applied = Vec::with_capacity(self.ops.len());
+        for op in &self.ops {
+            let status = self.apply_one(op, options)?;
+            let done = Applied {
+                op: op.clone(),
+                status,
+            };
+            report::applied(&done, options.dry_run);
+            applied.push(done);
+        }
+        Ok(applied)
+    }
+
+    /// Checks every written file whose extension names a [`Format`].
+    pub fn validate(&self) -> Result<(), MvpError> {
+        for op in &self.ops {
+            let FileOp::Write { path, contents } = op else {
+                continue;
+            };
+            let Some(format) = Format::from_path(path) else {
+                continue;
+            };
+            format
+                .validate(contents)
+                .map_err(|message| MvpError::InvalidOutput {
+                    path: self.root.join(path),
+                    format,
+                    message,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Fails if an operation would leave the plan root or, under
+    /// [`Conflict::Fail`], replace a file with different contents — on disk
+    /// or as an earlier operation of the plan leaves it.
+    fn preflight(&self, options: &ApplyOptions) -> Result<(), MvpError> {
+        let mut planned: HashMap<&Path, String> = HashMap::new();
+        for op in &self.ops {
+            ensure_relative(op.path())?;
+            if options.on_conflict != Conflict::Fail {
+                continue;
+            }
+            let current = |planned: &HashMap<&Path, String>, path: &Path| {
+                planned
+                    .get(path)
+                    .cloned()
+                    .or_else(|| fs::read_to_string(self.root.join(path)).ok())
+            };
+            match op {
+                FileOp::Write { path, contents } => {
+                    if current(&planned, path).is_some_and(|existing| existing != *contents) {
+                        return Err(MvpError::FileExists {
+                            path: self.root.join(path),
+                        });
+                    }
+                    planned.insert(path, contents.clone());
+                }
+                FileOp::Append { path, contents } => {
+                    let appended = current(&planned, path).unwrap_or_default() + contents.as_str();
+                    planned.insert(path, appended);
+                }
+                FileOp::Run { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_one(&self, op: &FileOp, options: &ApplyOptions) -> Result<Status, MvpError> {
+        let target = self.root.join(op.path());
+        match op {
+            FileOp::Write { contents, .. } => {
+                let status = match fs::read(&target) {
+                    Ok(existing) if existing == contents.as_bytes() => Status::Unchanged,
+                    Ok(_) => match options.on_conflict {
+                        Conflict::Overwrite => Status::Overwritten,
+                        Conflict::Skip => Status::Skipped,
+                        Conflict::Fail => return Err(MvpError::FileExists { path: target }),
+                    },
+                    Err(_) => Status::Created,
+                };
+                if !options.dry_run && matches!(status, Status::Created | Status::Overwritten) {
+                    write_file(&target, contents).map_err(|source| MvpError::WriteFailed {
+                        path: target.clone(),
+                        source,
+                    })?;
+                }
+                Ok(status)
+            }
+            FileOp::Append { contents, .. } => {
+                if !options.dry_run {
+                    append_file(&target, contents).map_err(|source| MvpError::WriteFailed {
+                        path: target.clone(),
+                        source,
+                    })?;
+                }
+                Ok(Status::Appended)
+            }
+            FileOp::Run { program, args, .. } => {
+                if !options.dry_run {
+                    let status = Command::new(program)
+                        .args(args)
+                        .current_dir(&target)
+                        .status()?;
+                    if !status.success() {
+                        return Err(MvpError::CommandFailed {
+                            command: format!("{} {}", program, args.join(" ")),
+                            status: status.to_string(),
+                        });
+                    }
+                }
+                Ok(Status::Ran)
+            }
+        }
+    }
+}
+
+fn write_file(target: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(target, contents)
+}
+
+fn append_file(target: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(target)?
+        .write_all(contents.as_bytes())
+}
+
+/// Rejects absolute paths and `..`, so no operation can leave the plan root.
+fn ensure_relative(path: &Path) -> Result<(), MvpError> {
+    if path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(())
+    } else {
+        Err(MvpError::Custom(format!(
+            "Refusing to touch {} outside the target directory",
+            path.display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn dry_run_reports_without_writing() {
+        let dir = tempdir().unwrap();
+        let mut plan = Plan::new(dir.path());
+        plan.write("a/b.txt", "hello");
+
+        let applied = plan
+            .apply(&ApplyOptions {
+                dry_run: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(applied[0].status, Status::Created);
+        assert!(!dir.path().join("a/b.txt").exists());
+    }
+
+    #[test]
+    fn conflicts_follow_policy() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("x.txt"), "old").unwrap();
+        let mut plan = Plan::new(dir.path());
+        plan.write("x.txt", "new");
+
+        let skip = ApplyOptions {
+            on_conflict: Conflict::Skip,
+            ..Default::default()
+        };
+        assert_eq!(plan.apply(&skip).unwrap()[0].status, Status::Skipped);
+        assert_eq!(fs::read_to_string(dir.path().join("x.txt")).unwrap(), "old");
+
+        let fail = ApplyOptions {
+            on_conflict: Conflict::Fail,
+            ..Default::default()
+        };
+        assert!(plan.apply(&fail).is_err());
+
+        // 冲突在写任何文件之前就要报出来
+        let mut late = Plan::new(dir.path());
+        late.write("first.txt", "1");
+        late.write("x.txt", "newer");
+        let err = late.apply(&fail).unwrap_err();
+        assert!(matches!(err, MvpError::FileExists { .. }), "{err}");
+        assert!(!dir.path().join("first.txt").exists());
+
+        assert_eq!(
+            plan.apply(&ApplyOptions::default()).unwrap()[0].status,
+            Status::Overwritten
+        );
+        assert_eq!(fs::read_to_string(dir.path().join("x.txt")).unwrap(), "new");
+    }
+
+    #[test]
+    fn invalid_config_stops_the_plan_before_writing() {
+        let dir = tempdir().unwrap();
+        let mut plan = Plan::new(dir.path());
+        plan.write("a.txt", "fine");
+        plan.write(".vscode/settings.json", "{\n  // ok\n  \"a\": 1\n}");
+        plan.write("Cargo.toml", "[package]\nname = \"O\"Brien\"\n");
+
+        let err = plan.apply(&ApplyOptions::default()).unwrap_err();
+        assert!(
+            matches!(&err, MvpError::InvalidOutput { path, .. } if path.ends_with("Cargo.toml")),
+            "{err}"
+        );
+        assert!(err.to_string().contains("line 2"), "{err}");
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn nested_plans_are_rerooted_and_confined() {
+        let mut plan = Plan::new("root");
+        let mut child = plan.child("demo");
+        child.write("README.md", "hi");
+        plan.nest("demo", child);
+        assert_eq!(plan.ops()[0].path(), Path::new("demo/README.md"));
+
+        let dir = tempdir().unwrap();
+        let mut escape = Plan::new(dir.path());
+        escape.write("inside.txt", "yes");
+        escape.write("../escape", "no");
+        assert!(escape.apply(&ApplyOptions::default()).is_err());
+        assert!(!dir.path().join("inside.txt").exists());
+    }
+}
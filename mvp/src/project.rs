@@ -0,0 +1,279 @@
+//! What `mvp` can tell about the project it runs in, handed to templates as
+//! `project`:
+//!
+//! ```text
+//! {% if project.package.lib %}cargo doc --open{% endif %}
+//! {% for member in project.workspace.members %}{{ member.name }}{% endfor %}
+//! {{ project.git.remote }}
+//! ```
+//!
+//! Anything that can't be found is left empty rather than failing the run:
+//! `mvp` also runs in directories that are not (yet) Cargo projects.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{add::manifest::Manifest, error::MvpError};
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Project {
+    /// The package at the target; empty for a virtual manifest or no
+    /// manifest at all.
+    pub package: Package,
+    pub workspace: Workspace,
+    pub git: Git,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Inherited from `[workspace.package]` when the package says so.
+    pub edition: String,
+    /// Directory relative to the workspace root, `/`-separated; empty for
+    /// the root package.
+    pub path: String,
+    /// Whether there is a library target.
+    pub lib: bool,
+    /// Binary target names.
+    pub bins: Vec<String>,
+    /// `[features]` names, `default` excluded.
+    pub features: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Workspace {
+    /// Absolute path of the workspace root; empty outside a workspace.
+    pub root: String,
+    pub members: Vec<Package>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Git {
+    /// URL of `origin`, or of the first remote.
+    pub remote: String,
+    pub user_name: String,
+    pub user_email: String,
+}
+
+impl Project {
+    /// Inspects `target` and the workspace around it.
+    pub fn inspect(target: &Path) -> Self {
+        let Ok(target) = std::path::absolute(target) else {
+            return Self::default();
+        };
+        let workspace = workspace_root(&target);
+        let root = workspace.as_deref().unwrap_or(&target);
+        let root_manifest = read_manifest(root, Path::new("Cargo.toml")).ok();
+        let inspect = |dir: &Path| {
+            let relative = dir.strip_prefix(root).unwrap_or(Path::new(""));
+            let manifest = read_manifest(root, &relative.join("Cargo.toml")).ok()?;
+            package(dir, relative, &manifest, root_manifest.as_ref())
+        };
+
+        let members = match &workspace {
+            Some(root) => member_manifests(root)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|path| inspect(&root.join(path.parent()?)))
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            package: inspect(&target).unwrap_or_default(),
+            workspace: Workspace {
+                root: workspace
+                    .map(|root| root.display().to_string())
+                    .unwrap_or_default(),
+                members,
+            },
+            git: Git::inspect(&target),
+        }
+    }
+}
+
+/// `None` when `manifest` has no `[package]`.
+fn package(
+    dir: &Path,
+    relative: &Path,
+    manifest: &Manifest,
+    root_manifest: Option<&Manifest>,
+) -> Option<Package> {
+    let name = manifest.package_name()?;
+    // 继承自 workspace 的字段在成员清单里不是字符串，去根清单里找
+    let field = |key: &str| {
+        manifest
+            .package_field(key)
+            .or_else(|| root_manifest?.workspace_package_field(key))
+            .unwrap_or_default()
+            .to_owned()
+    };
+    Some(Package {
+        name: name.to_owned(),
+        version: field("version"),
+        edition: field("edition"),
+        path: relative
+            .iter()
+            .map(|part| part.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        lib: manifest.has_lib(dir),
+        bins: manifest.binary_names(dir),
+        features: manifest
+            .features()
+            .into_iter()
+            .map(|f| f.name)
+            .filter(|name| name != "default")
+            .collect(),
+    })
+}
+
+impl Git {
+    fn inspect(target: &Path) -> Self {
+        // 目标目录可能还不存在（比如 `mvp init`），git 从最近的已有目录开始找
+        let Some(dir) = target.ancestors().find(|dir| dir.is_dir()) else {
+            return Self::default();
+        };
+        let remote = git(dir, &["remote", "get-url", "origin"]).or_else(|| {
+            let first = git(dir, &["remote"])?.lines().next()?.to_owned();
+            git(dir, &["remote", "get-url", &first])
+        });
+        Self {
+            remote: remote.unwrap_or_default(),
+            user_name: git(dir, &["config", "user.name"]).unwrap_or_default(),
+            user_email: git(dir, &["config", "user.email"]).unwrap_or_default(),
+        }
+    }
+}
+
+/// Trimmed stdout of a successful `git` command.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (!text.is_empty()).then_some(text)
+}
+
+pub fn read_manifest(root: &Path, path: &Path) -> Result<Manifest, MvpError> {
+    let text = std::fs::read_to_string(root.join(path))?;
+    Manifest::parse(path, &text)
+}
+
+/// The closest directory at or above `dir` whose `Cargo.toml` has a
+/// `[workspace]` table.
+pub fn workspace_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| {
+            std::fs::read_to_string(dir.join("Cargo.toml"))
+                .ok()
+                .and_then(|text| text.parse::<toml_edit::DocumentMut>().ok())
+                .is_some_and(|doc| doc.contains_key("workspace"))
+        })
+        .map(Path::to_path_buf)
+}
+
+/// `Cargo.toml` of every workspace member, relative to the workspace root:
+/// the `members` globs minus anything under an `exclude` entry.
+pub fn member_manifests(workspace: &Path) -> Result<Vec<PathBuf>, MvpError> {
+    let root = workspace.join("Cargo.toml");
+    if !root.is_file() {
+        return Ok(Vec::new());
+    }
+    let manifest = read_manifest(workspace, Path::new("Cargo.toml"))?;
+    let excludes: Vec<PathBuf> = manifest
+        .workspace_excludes()
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    let mut members = Vec::new();
+    for pattern in manifest.workspace_members() {
+        let pattern = workspace.join(&pattern).join("Cargo.toml");
+        let paths = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| MvpError::Custom(format!("Invalid workspace member pattern: {e}")))?;
+        for path in paths.flatten() {
+            if let Ok(relative) = path.strip_prefix(workspace)
+                && !excludes.iter().any(|dir| relative.starts_with(dir))
+            {
+                members.push(relative.to_path_buf());
+            }
+        }
+    }
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn inspects_workspace_members_and_targets() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let write = |path: &str, text: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nedition = \"2024\"\n",
+        );
+        write(
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion = \"0.2.0\"\nedition.workspace = true\n\n\
+             [features]\ndefault = [\"std\"]\nstd = []\n",
+        );
+        write("crates/core/src/lib.rs", "");
+        write(
+            "crates/cli/Cargo.toml",
+            "[package]\nname = \"cli\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [[bin]]\nname = \"tool\"\npath = \"src/tool.rs\"\n",
+        );
+        write("crates/cli/src/main.rs", "fn main() {}");
+        write("crates/cli/src/bin/extra.rs", "fn main() {}");
+
+        let project = Project::inspect(&root.join("crates/core"));
+        assert_eq!(
+            project.package,
+            Package {
+                name: "core".into(),
+                version: "0.2.0".into(),
+                edition: "2024".into(),
+                path: "crates/core".into(),
+                lib: true,
+                bins: Vec::new(),
+                features: vec!["std".into()],
+            }
+        );
+        assert_eq!(project.workspace.root, root.display().to_string());
+        let members: Vec<_> = project.workspace.members.iter().map(|m| &m.name).collect();
+        assert_eq!(members, ["cli", "core"]);
+        assert_eq!(project.workspace.members[0].bins, ["cli", "extra", "tool"]);
+        assert!(!project.workspace.members[0].lib);
+
+        // 虚拟清单没有 package
+        assert_eq!(Project::inspect(root).package, Package::default());
+        assert_eq!(
+            Project::inspect(&root.join("missing")).package,
+            Package::default()
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tera::Tera;
+
+use crate::{
+    error::MvpError,
+    i18n::Message,
+    plan::Plan,
+    project::{member_manifests, read_manifest, workspace_root},
+    report,
+    templates::contexts::{ChangelogContext, ReleaseContext, ReleaseSection, TemplateContext},
+};
+
+/// Which part of `MAJOR.MINOR.PATCH` `mvp release` increments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// The version after `bump`; a pre-release such as `1.0.0-rc.1` is released
+/// as `1.0.0` by a patch bump.
+pub fn bump(version: &str, bump: Bump) -> Result<String, MvpError> {
+    let (core, pre) = match version.split_once(['-', '+']) {
+        Some((core, rest)) => (core, !rest.is_empty()),
+        None => (version, false),
+    };
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()
+        .filter(|parts: &Vec<u64>| parts.len() == 3)
+        .ok_or_else(|| {
+            MvpError::Custom(format!("`{version}` is not a MAJOR.MINOR.PATCH version"))
+        })?;
+    let (major, minor, patch) = (parts[0], parts[1], parts[2]);
+    Ok(match bump {
+        Bump::Major => format!("{}.0.0", major + 1),
+        Bump::Minor => format!("{major}.{}.0", minor + 1),
+        Bump::Patch if pre => format!("{major}.{minor}.{patch}"),
+        Bump::Patch => format!("{major}.{minor}.{}", patch + 1),
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReleaseOptions {
+    pub bump: Bump,
+    /// Commit the release and create an annotated tag.
+    pub tag: bool,
+    /// Only preview the release, so a dirty working tree is no reason to
+    /// stop.
+    pub dry_run: bool,
+}
+
+/// Plans a release of the package at `target` from the local git history:
+/// bumps its version (and the requirements of workspace members depending on
+/// it), adds a `CHANGELOG.md` section built from conventional commits since
+/// the last tag, then commits and tags.
+///
+/// The plan is rooted at the workspace root so member manifests can be
+/// edited; nothing talks to a remote.
+pub fn plan(target: &Path, tera: &Tera, options: ReleaseOptions) -> Result<Plan, MvpError> {
+    let target = std::path::absolute(target)?;
+    let workspace = workspace_root(&target).unwrap_or_else(|| target.clone());
+    let package_dir = target
+        .strip_prefix(&workspace)
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+    let mut plan = Plan::new(&workspace);
+
+    let manifest_path = package_dir.join("Cargo.toml");
+    let mut manifest = read_manifest(&workspace, &manifest_path)?;
+    if manifest.inherits_version() {
+        return Err(MvpError::Custom(format!(
+            "{} inherits its version from the workspace; release from {}",
+            manifest_path.display(),
+            workspace.display()
+        )));
+    }
+    let current = manifest
+        .version()
+        .ok_or_else(|| MvpError::Custom(format!("{} has no version", manifest_path.display())))?
+        .to_owned();
+    let version = bump(&current, options.bump)?;
+    let name = manifest.package_name().map(str::to_owned);
+    report::progress(Message::Releasing {
+        package: name.as_deref().unwrap_or("workspace"),
+        from: &current,
+        to: &version,
+    });
+
+    // 被改版本号的包：本包，以及继承 workspace.package.version 的成员
+    let mut bumped: Vec<String> = name.iter().cloned().collect();
+    let bumps_workspace = manifest.package_field("version").is_none();
+    manifest.set_version(&version);
+
+    let mut edited = vec![(manifest_path.clone(), manifest)];
+    for member in member_manifests(&workspace)? {
+        if member == manifest_path {
+            continue;
+        }
+        let manifest = read_manifest(&workspace, &member)?;
+        if bumps_workspace && manifest.inherits_version() {
+            bumped.extend(manifest.package_name().map(str::to_owned));
+        }
+        edited.push((member, manifest));
+    }
+    if manifest_path != Path::new("Cargo.toml") && workspace.join("Cargo.toml").is_file() {
+        edited.push((
+            PathBuf::from("Cargo.toml"),
+            read_manifest(&workspace, Path::new("Cargo.toml"))?,
+        ));
+    }
+    let mut changed = Vec::new();
+    for (path, mut manifest) in edited {
+        let mut dirty = path == manifest_path;
+        for name in &bumped {
+            dirty |= manifest.set_dependency_version(name, &version);
+        }
+        if dirty {
+            plan.write(&path, manifest.to_string());
+            changed.push(path);
+        }
+    }
+
+    // 单包或 workspace 根用 vX.Y.Z，成员包用 <name>-vX.Y.Z
+    let tag_prefix = match (&name, package_dir.as_os_str().is_empty()) {
+        (Some(name), false) => format!("{name}-v"),
+        _ => "v".to_owned(),
+    };
+    let tag = format!("{tag_prefix}{version}");
+    let commits = commits_since_last_tag(&workspace, &tag_prefix, &package_dir)?;
+    let release = ReleaseContext {
+        version: version.clone(),
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        sections: sections(&commits),
+    }
+    .render(tera, "CHANGELOG-release.md")?;
+
+    let changelog_path = package_dir.join("CHANGELOG.md");
+    let changelog = match std::fs::read_to_string(workspace.join(&changelog_path)) {
+        Ok(text) => text,
+        Err(_) => ChangelogContext::default().render(tera, "CHANGELOG.md")?,
+    };
+    plan.write(&changelog_path, insert_release(&changelog, &release));
+    changed.push(changelog_path);
+
+    if options.tag && !options.dry_run {
+        let status = git(
+            &workspace,
+            &["status", "--porcelain", "--untracked-files=no"],
+        )?;
+        if !status.trim().is_empty() {
+            return Err(MvpError::Custom(
+                "The working tree has uncommitted changes; commit or stash them first".to_owned(),
+            ));
+        }
+    }
+    if options.tag {
+        let mut add = vec!["add".to_owned(), "--".to_owned()];
+        add.extend(changed.iter().map(|path| path.display().to_string()));
+        plan.run("git", add);
+        plan.run(
+            "git",
+            ["commit", "--quiet", "-m", &format!("chore(release): {tag}")],
+        );
+        plan.run("git", ["tag", "-a", &tag, "-m", &format!("Release {tag}")]);
+    }
+    Ok(plan)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, MvpError> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        return Err(MvpError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            status: output.status.to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commit messages (subject and body) since the last `<prefix>*` tag, limited
+/// to `package_dir` when it is a workspace member.
+fn commits_since_last_tag(
+    workspace: &Path,
+    prefix: &str,
+    package_dir: &Path,
+) -> Result<Vec<(String, String)>, MvpError> {
+    let pattern = format!("{prefix}*");
+    let last_tag = git(
+        workspace,
+        &["describe", "--tags", "--abbrev=0", "--match", &pattern],
+    )
+    .ok()
+    .map(|tag| tag.trim().to_owned());
+    let range = last_tag.map_or_else(|| "HEAD".to_owned(), |tag| format!("{tag}..HEAD"));
+    let mut args = vec!["log", "--format=%s%x1f%b%x1e", range.as_str()];
+    let package = package_dir.to_string_lossy();
+    if !package.is_empty() {
+        args.extend(["--", &package]);
+    }
+    let log = git(workspace, &args)?;
+    Ok(log
+        .split('\x1e')
+        .filter_map(|entry| {
+            let (subject, body) = entry.trim_start_matches('\n').split_once('\x1f')?;
+            Some((subject.to_owned(), body.to_owned()))
+        })
+        .collect())
+}
+
+/// Keep-a-Changelog sections for conventional commits; other commits
+/// (`chore`, `docs`, `test`, non-conventional subjects) are left out.
+fn sections(commits: &[(String, String)]) -> Vec<ReleaseSection> {
+    const TITLES: [&str; 6] = [
+        "Added",
+        "Changed",
+        "Deprecated",
+        "Removed",
+        "Fixed",
+        "Security",
+    ];
+    let mut sections: Vec<ReleaseSection> = TITLES
+        .iter()
+        .map(|title| ReleaseSection {
+            title: (*title).to_owned(),
+            entries: Vec::new(),
+        })
+        .collect();
+    for (subject, body) in commits {
+        let Some((prefix, description)) = subject.split_once(": ") else {
+            continue;
+        };
+        let breaking = prefix.ends_with('!') || body.contains("BREAKING CHANGE");
+        let prefix = prefix.trim_end_matches('!');
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (kind, scope.strip_suffix(')')),
+            None => (prefix, None),
+        };
+        if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+            continue;
+        }
+        let title = match kind {
+            "feat" => "Added",
+            "fix" => "Fixed",
+            "perf" | "refactor" => "Changed",
+            "deprecate" => "Deprecated",
+            "remove" => "Removed",
+            "security" => "Security",
+            _ if breaking => "Changed",
+            _ => continue,
+        };
+        let mut entry = String::new();
+        if breaking {
+            entry.push_str("**BREAKING:** ");
+        }
+        if let Some(scope) = scope {
+            entry.push_str(&format!("**{scope}:** "));
+        }
+        entry.push_str(description.trim());
+        if let Some(section) = sections.iter_mut().find(|s| s.title == title) {
+            section.entries.push(entry);
+        }
+    }
+    sections.retain(|s| !s.entries.is_empty());
+    sections
+}
+
+/// Inserts `release` right below `## [Unreleased]`, moving whatever was
+/// listed as unreleased into it ahead of the generated `###` sections, so
+/// the notes don't read as part of the last one.
+fn insert_release(changelog: &str, release: &str) -> String {
+    let lines: Vec<&str> = changelog.split_inclusive('\n').collect();
+    let is_heading = |line: &&str| line.starts_with("## ");
+    let unreleased = lines
+        .iter()
+        .position(|line| line.to_ascii_lowercase().starts_with("## [unreleased]"));
+    let (head, notes, tail) = match unreleased {
+        Some(at) => {
+            let end = lines[at + 1..]
+                .iter()
+                .position(is_heading)
+                .map_or(lines.len(), |i| at + 1 + i);
+            (&lines[..=at], &lines[at + 1..end], &lines[end..])
+        }
+        None => {
+            let at = lines.iter().position(is_heading).unwrap_or(lines.len());
+            (&lines[..at], &lines[..0], &lines[at..])
+        }
+    };
+
+    let mut out = head.concat();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push('\n');
+    let (heading, sections) = release.split_once('\n').unwrap_or((release, ""));
+    out.push_str(heading);
+    out.push('\n');
+    let notes = notes.concat();
+    if !notes.trim().is_empty() {
+        out.push('\n');
+        out.push_str(notes.trim());
+        out.push('\n');
+    }
+    if !sections.trim().is_empty() {
+        out.push('\n');
+        out.push_str(sections.trim());
+        out.push('\n');
+    }
+    if !tail.is_empty() {
+        out.push('\n');
+        out.push_str(&tail.concat());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn versions_bump_by_part() {
+        assert_eq!(bump("0.1.9", Bump::Patch).unwrap(), "0.1.10");
+        assert_eq!(bump("0.1.9", Bump::Minor).unwrap(), "0.2.0");
+        assert_eq!(bump("0.1.9", Bump::Major).unwrap(), "1.0.0");
+        assert_eq!(bump("1.0.0-rc.1", Bump::Patch).unwrap(), "1.0.0");
+        assert!(bump("1.0", Bump::Patch).is_err());
+    }
+
+    #[test]
+    fn conventional_commits_become_sections() {
+        let commits = [
+            ("feat(cli): add --json".to_owned(), String::new()),
+            ("fix: handle empty input".to_owned(), String::new()),
+            ("refactor!: drop old API".to_owned(), String::new()),
+            ("chore: bump deps".to_owned(), String::new()),
+            ("[user-031] Route output".to_owned(), String::new()),
+        ];
+        let sections = sections(&commits);
+        let titles: Vec<_> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Added", "Changed", "Fixed"]);
+        assert_eq!(sections[0].entries, ["**cli:** add --json"]);
+        assert_eq!(sections[1].entries, ["**BREAKING:** drop old API"]);
+    }
+
+    #[test]
+    fn release_goes_below_unreleased_and_takes_its_notes() {
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n- Hand-written note\n\n## [0.1.0] - 2024-01-01\n\n- First\n";
+        let release = "## [0.2.0] - 2024-02-01\n\n### Fixed\n\n- A bug\n";
+        assert_eq!(
+            insert_release(changelog, release),
+            "# Changelog\n\n## [Unreleased]\n\n## [0.2.0] - 2024-02-01\n\n- Hand-written note\n\n### Fixed\n\n- A bug\n\n## [0.1.0] - 2024-01-01\n\n- First\n"
+        );
+    }
+
+    #[test]
+    fn dirty_tree_only_stops_a_real_release() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        run(&["init", "--quiet"]);
+        run(&["add", "Cargo.toml"]);
+        run(&["commit", "--quiet", "-m", "feat: start"]);
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.1\"\n",
+        )
+        .unwrap();
+
+        let tera = crate::templates::embedded().unwrap();
+        let mut options = ReleaseOptions {
+            bump: Bump::Minor,
+            tag: true,
+            dry_run: true,
+        };
+        let plan = plan(root, &tera, options).unwrap();
+        assert!(
+            plan.ops()
+                .iter()
+                .any(|op| op.path() == Path::new("CHANGELOG.md"))
+        );
+        options.dry_run = false;
+        assert!(super::plan(root, &tera, options).is_err());
+    }
+}
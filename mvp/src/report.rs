@@ -0,0 +1,308 @@
+use std::{path::Path, sync::RwLock};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    error::MvpError,
+    i18n::Message,
+    plan::{Applied, FileOp, Status},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON event per line
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only
+    #[default]
+    Quiet,
+    /// Progress and applied operations
+    Normal,
+    /// Also every planned operation
+    Verbose,
+    /// Also internal details such as loaded templates
+    Debug,
+}
+
+impl Verbosity {
+    /// Maps `--quiet` and the number of `-v` flags to a level.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+}
+
+/// Everything `mvp` reports while running.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Planned {
+        op: &'static str,
+        path: &'a Path,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+    },
+    Applied {
+        op: &'static str,
+        path: &'a Path,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+        status: Status,
+        dry_run: bool,
+    },
+    /// How one member fared in a `--workspace` run.
+    Member {
+        package: &'a str,
+        path: &'a Path,
+        changed: usize,
+        unchanged: usize,
+    },
+    Progress {
+        message: &'a str,
+    },
+    Debug {
+        message: &'a str,
+    },
+    Error {
+        error: Value,
+    },
+}
+
+/// Decides which events are shown and how.
+///
+/// The library is silent by default; the `mvp` binary installs a reporter
+/// built from its command-line flags with [`set_reporter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reporter {
+    pub format: OutputFormat,
+    pub verbosity: Verbosity,
+}
+
+impl Reporter {
+    pub const fn new(format: OutputFormat, verbosity: Verbosity) -> Self {
+        Self { format, verbosity }
+    }
+
+    /// The line to print for `event`, or `None` when it is filtered out.
+    ///
+    /// JSON output always carries planned and applied operations, since
+    /// tools consuming it need the full picture; `--quiet` only silences the
+    /// chatter around them.
+    pub fn render(&self, event: &Event) -> Option<String> {
+        let min = match event {
+            Event::Error { .. } => Verbosity::Quiet,
+            Event::Planned { .. } if self.format == OutputFormat::Json => Verbosity::Quiet,
+            Event::Applied { .. } | Event::Member { .. } if self.format == OutputFormat::Json => {
+                Verbosity::Quiet
+            }
+            Event::Applied { .. } | Event::Member { .. } | Event::Progress { .. } => {
+                Verbosity::Normal
+            }
+            Event::Planned { .. } => Verbosity::Verbose,
+            Event::Debug { .. } => Verbosity::Debug,
+        };
+        if self.verbosity < min {
+            return None;
+        }
+        match self.format {
+            OutputFormat::Json => serde_json::to_string(event).ok(),
+            OutputFormat::Text => Some(match event {
+                Event::Planned { op, path, command } => {
+                    let target = match command {
+                        Some(command) => format!("`{command}`"),
+                        None => path.display().to_string(),
+                    };
+                    Message::Planned {
+                        op,
+                        target: &target,
+                    }
+                    .to_string()
+                }
+                Event::Applied {
+                    path,
+                    command,
+                    status,
+                    dry_run,
+                    ..
+                } => {
+                    let target = match command {
+                        Some(command) => format!("`{command}`"),
+                        None => path.display().to_string(),
+                    };
+                    Message::Applied {
+                        status: *status,
+                        target: &target,
+                        dry_run: *dry_run,
+                    }
+                    .to_string()
+                }
+                Event::Member {
+                    package,
+                    changed,
+                    unchanged,
+                    ..
+                } => Message::MemberDone {
+                    package,
+                    changed: *changed,
+                    unchanged: *unchanged,
+                }
+                .to_string(),
+                Event::Progress { message } => message.to_string(),
+                Event::Debug { message } => format!("debug: {message}"),
+                Event::Error { error } => error["message"].as_str().unwrap_or_default().to_owned(),
+            }),
+        }
+    }
+
+    pub fn emit(&self, event: &Event) {
+        let Some(line) = self.render(event) else {
+            return;
+        };
+        // 文本模式下错误走 stderr，其余都走 stdout，方便编辑器按行读取
+        if matches!(event, Event::Error { .. }) && self.format == OutputFormat::Text {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+static REPORTER: RwLock<Reporter> =
+    RwLock::new(Reporter::new(OutputFormat::Text, Verbosity::Quiet));
+
+/// Installs the process-wide reporter.
+pub fn set_reporter(reporter: Reporter) {
+    *REPORTER.write().unwrap_or_else(|e| e.into_inner()) = reporter;
+}
+
+pub fn reporter() -> Reporter {
+    *REPORTER.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn op_fields(op: &FileOp) -> (&'static str, &Path, Option<String>) {
+    match op {
+        FileOp::Write { path, .. } => ("write", path, None),
+        FileOp::Append { path, .. } => ("append", path, None),
+        FileOp::Run { program, args, dir } => {
+            ("run", dir, Some(format!("{} {}", program, args.join(" "))))
+        }
+    }
+}
+
+pub fn planned(op: &FileOp) {
+    let (op, path, command) = op_fields(op);
+    reporter().emit(&Event::Planned { op, path, command });
+}
+
+pub fn applied(applied: &Applied, dry_run: bool) {
+    let (op, path, command) = op_fields(&applied.op);
+    reporter().emit(&Event::Applied {
+        op,
+        path,
+        command,
+        status: applied.status,
+        dry_run,
+    });
+}
+
+/// Sums up the operations applied in one workspace member.
+pub fn member(package: &str, path: &Path, applied: &[Applied]) {
+    let unchanged = applied
+        .iter()
+        .filter(|a| matches!(a.status, Status::Unchanged | Status::Skipped))
+        .count();
+    reporter().emit(&Event::Member {
+        package,
+        path,
+        changed: applied.len() - unchanged,
+        unchanged,
+    });
+}
+
+/// Reports progress; pass an [`i18n::Message`](crate::i18n::Message) for
+/// text users should see in their language.
+pub fn progress(message: impl std::fmt::Display) {
+    reporter().emit(&Event::Progress {
+        message: &message.to_string(),
+    });
+}
+
+pub fn debug(message: impl AsRef<str>) {
+    reporter().emit(&Event::Debug {
+        message: message.as_ref(),
+    });
+}
+
+/// Reports a failure: the caret report in text mode, a JSON event otherwise.
+pub fn error(err: &MvpError) {
+    let reporter = reporter();
+    match reporter.format {
+        OutputFormat::Text => eprint!("{}", err.report()),
+        OutputFormat::Json => reporter.emit(&Event::Error {
+            error: err.to_json()["error"].take(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied_event(path: &Path) -> Event<'_> {
+        Event::Applied {
+            op: "write",
+            path,
+            command: None,
+            status: Status::Created,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn text_output_respects_verbosity() {
+        let path = Path::new("rustfmt.toml");
+        let planned = Event::Planned {
+            op: "write",
+            path,
+            command: None,
+        };
+
+        let normal = Reporter::new(OutputFormat::Text, Verbosity::Normal);
+        assert_eq!(
+            normal.render(&applied_event(path)).as_deref(),
+            Some("Created rustfmt.toml")
+        );
+        assert_eq!(normal.render(&planned), None);
+
+        let verbose = Reporter::new(OutputFormat::Text, Verbosity::Verbose);
+        assert_eq!(
+            verbose.render(&planned).as_deref(),
+            Some("Planned write rustfmt.toml")
+        );
+
+        let quiet = Reporter::new(OutputFormat::Text, Verbosity::Quiet);
+        assert_eq!(quiet.render(&applied_event(path)), None);
+    }
+
+    #[test]
+    fn json_output_keeps_file_events_when_quiet() {
+        let quiet = Reporter::new(OutputFormat::Json, Verbosity::Quiet);
+        let line = quiet.render(&applied_event(Path::new("a.txt"))).unwrap();
+        let event: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(event["event"], "applied");
+        assert_eq!(event["status"], "created");
+        assert_eq!(event["path"], "a.txt");
+        assert_eq!(quiet.render(&Event::Progress { message: "hi" }), None);
+    }
+}
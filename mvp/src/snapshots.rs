@@ -0,0 +1,220 @@
+//! Golden-file tests: every embedded template and every built-in strategy is
+//! rendered into an in-memory tree and compared with the files committed
+//! under `snapshots/`.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to rewrite the golden files after an
+//! intended change, then review the diff. Each golden file carries a `.snap`
+//! suffix so a snapshot of `.gitignore` or `Cargo.toml` means nothing to git
+//! or cargo.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use tempfile::{TempDir, tempdir};
+use tera::Context;
+
+use crate::{
+    Generator,
+    add::{archetype::Archetype, context::AddStrategyFactory},
+    config::Config,
+    i18n::Lang,
+    plan::{FileOp, Plan},
+    templates::{self, TEMPLATE_MAP, TEMPLATES, contexts},
+};
+
+/// Pseudo-file listing the commands a plan runs, e.g. `cargo new`.
+const COMMANDS: &str = "$commands";
+
+/// Year and author every snapshot is rendered with.
+fn fixed_context() -> Context {
+    let mut context = Context::new();
+    context.insert("year", &2024);
+    context.insert("author", "Snapshot Author");
+    Config::default().insert_into(&mut context);
+    context
+}
+
+/// A small library crate strategies can read from.
+fn fixture() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2024\"\ndescription = \
+         \"A demo crate\"\nlicense = \"MIT OR Apache-2.0\"\n\n[dependencies]\nserde_json = \
+         \"1\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn greet(name: &str) -> String {\n    format!(\"Hello, {name}!\")\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+/// The files a plan would leave behind, plus its commands in [`COMMANDS`].
+fn tree(plan: &Plan) -> BTreeMap<String, String> {
+    let mut tree = BTreeMap::new();
+    for op in plan.ops() {
+        match op {
+            FileOp::Write { path, contents } => {
+                tree.insert(slash_path(path), contents.clone());
+            }
+            FileOp::Append { path, contents } => {
+                tree.entry(slash_path(path)).or_default().push_str(contents)
+            }
+            FileOp::Run { program, args, dir } => {
+                let line = format!("{}$ {program} {}\n", slash_path(dir), args.join(" "));
+                tree.entry(COMMANDS.to_owned()).or_default().push_str(&line);
+            }
+        }
+    }
+    tree
+}
+
+fn slash_path(path: &Path) -> String {
+    path.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn snapshot_dir(kind: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(kind)
+}
+
+fn golden_files(dir: &Path, prefix: &str, files: &mut BTreeMap<String, String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.map(Result::unwrap) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = format!("{prefix}{name}");
+        if entry.file_type().unwrap().is_dir() {
+            golden_files(&entry.path(), &format!("{path}/"), files);
+        } else if let Some(path) = path.strip_suffix(".snap") {
+            files.insert(path.to_owned(), fs::read_to_string(entry.path()).unwrap());
+        }
+    }
+}
+
+/// Compares `actual` with the golden files in `dir`, or rewrites them when
+/// `UPDATE_SNAPSHOTS` is set. Returns one line per mismatch.
+fn check(dir: &Path, actual: &BTreeMap<String, String>) -> Vec<String> {
+    if env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v != "0") {
+        let _ = fs::remove_dir_all(dir);
+        for (path, contents) in actual {
+            let file = dir.join(format!("{path}.snap"));
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, contents).unwrap();
+        }
+        return Vec::new();
+    }
+    let mut expected = BTreeMap::new();
+    golden_files(dir, "", &mut expected);
+    let dir = dir.display();
+    let mut problems = Vec::new();
+    for (path, contents) in actual {
+        match expected.get(path) {
+            None => problems.push(format!("{dir}/{path}: no snapshot")),
+            Some(golden) if golden != contents => {
+                let line = golden
+                    .lines()
+                    .zip(contents.lines())
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(golden.lines().count().min(contents.lines().count()));
+                problems.push(format!(
+                    "{dir}/{path}: differs from line {}\n  expected: {:?}\n    actual: {:?}",
+                    line + 1,
+                    golden.lines().nth(line).unwrap_or_default(),
+                    contents.lines().nth(line).unwrap_or_default(),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for path in expected.keys().filter(|p| !actual.contains_key(*p)) {
+        problems.push(format!("{dir}/{path}: no longer generated"));
+    }
+    problems
+}
+
+fn assert_clean(problems: Vec<String>) {
+    assert!(
+        problems.is_empty(),
+        "{} snapshot(s) out of date; rerun with UPDATE_SNAPSHOTS=1 if this is intended:\n{}",
+        problems.len(),
+        problems.join("\n")
+    );
+}
+
+#[test]
+fn every_template_matches_its_snapshot() {
+    let mut actual = BTreeMap::new();
+    for (name, _) in TEMPLATE_MAP {
+        let mut context = contexts::default_context(name)
+            .expect("every template has a typed context")
+            .unwrap();
+        context.extend(fixed_context());
+        actual.insert(
+            (*name).to_owned(),
+            templates::render(&TEMPLATES, name, &context).unwrap(),
+        );
+    }
+    assert_clean(check(&snapshot_dir("templates"), &actual));
+}
+
+#[test]
+fn every_strategy_matches_its_snapshot() {
+    let factory = AddStrategyFactory::get_add_strategy_factory();
+    let mut cases: Vec<(String, &str, Vec<&str>)> = factory
+        .names()
+        .into_iter()
+        .map(|name| {
+            let args = match name {
+                "module" => vec!["greeting", "--tests"],
+                "bench" => vec!["greet"],
+                _ => Vec::new(),
+            };
+            (name.to_owned(), name, args)
+        })
+        .collect();
+    cases.push(("init-archetype-cli".to_owned(), "init", Vec::new()));
+    cases.push(("init-zh".to_owned(), "init", Vec::new()));
+
+    let mut problems = Vec::new();
+    for (case, strategy, args) in cases {
+        let fixture = fixture();
+        let mut context = fixed_context();
+        if strategy == "init" {
+            context.insert("init_values", &["hello"]);
+        }
+        if strategy == "docker" {
+            fs::write(fixture.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        }
+        if case == "init-archetype-cli" {
+            context.insert("archetype", &Archetype::Cli);
+        }
+        let plan = Generator::builder()
+            .target(fixture.path())
+            .config(Config::default())
+            .context(context)
+            .lang(if case.ends_with("-zh") {
+                Lang::Zh
+            } else {
+                Lang::En
+            })
+            .strategies([strategy])
+            .args(args)
+            .plan()
+            .unwrap_or_else(|e| panic!("{case}: {e}"));
+        problems.extend(check(&snapshot_dir("strategies").join(&case), &tree(&plan)));
+    }
+    assert_clean(problems);
+}
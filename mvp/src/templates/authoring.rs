@@ -0,0 +1,368 @@
+//! `mvp templates render|lint|watch`: trying out templates without
+//! rebuilding `mvp`.
+//!
+//! Templates from a directory are layered over the embedded ones, so a file
+//! with an embedded name overrides it and new files can `extend` or
+//! `include` embedded templates.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{RecursiveMode, Watcher};
+use serde_json::Value;
+use tera::{Context, Tera};
+
+use super::{contexts, embedded, is_partial, variables::rendered_variables};
+use crate::{
+    Generator,
+    config::Config,
+    error::MvpError,
+    plan::{ApplyOptions, Conflict, Plan},
+    project::Project,
+    report,
+};
+
+/// Templates to author against: the embedded ones with those of a
+/// directory layered over them.
+pub struct Loaded {
+    pub tera: Tera,
+    /// The files of the directory, or every embedded template without one.
+    /// Partials are loaded but never among the names.
+    pub names: Vec<String>,
+    /// Sources read from the directory, by template name.
+    sources: HashMap<String, String>,
+}
+
+impl Loaded {
+    /// Source of `name` as loaded: the directory's file, else the embedded
+    /// template it would override.
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.sources
+            .get(name)
+            .map(String::as_str)
+            .or_else(|| super::source(name))
+    }
+}
+
+/// Embedded templates plus those under `dir`.
+pub fn load(dir: Option<&Path>) -> Result<Loaded, MvpError> {
+    let mut tera = embedded()?;
+    let Some(dir) = dir else {
+        let mut names: Vec<String> = tera
+            .get_template_names()
+            .filter(|name| !is_partial(name))
+            .map(str::to_owned)
+            .collect();
+        names.sort();
+        return Ok(Loaded {
+            tera,
+            names,
+            sources: HashMap::new(),
+        });
+    };
+    let mut files = Vec::new();
+    template_files(dir, Path::new(""), &mut files)?;
+    let mut sources = Vec::new();
+    for path in &files {
+        let name = path
+            .iter()
+            .map(|part| part.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        sources.push((name, fs::read_to_string(dir.join(path))?));
+    }
+    tera.add_raw_templates(sources.iter().map(|(n, s)| (n.as_str(), s.as_str())))?;
+    let names = sources
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| !is_partial(name))
+        .cloned()
+        .collect();
+    Ok(Loaded {
+        tera,
+        names,
+        sources: sources.into_iter().collect(),
+    })
+}
+
+fn template_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), MvpError> {
+    let mut entries: Vec<_> = fs::read_dir(root.join(dir))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = dir.join(entry.file_name());
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            template_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Context for rendering `name` by hand: the typed default context when the
+/// template has one, the run's base context, the project in the current
+/// directory and config, then the values of `overrides` (a TOML file) on top.
+pub fn context(name: &str, overrides: Option<&Path>) -> Result<Context, MvpError> {
+    let mut context = match contexts::default_context(name) {
+        Some(typed) => typed?,
+        None => Context::new(),
+    };
+    context.extend(Generator::base_context());
+    context.insert("project", &Project::inspect(Path::new(".")));
+    Config::load()?.insert_into(&mut context);
+    if let Some(path) = overrides {
+        let text = fs::read_to_string(path)?;
+        let values: toml::Table = toml::from_str(&text).map_err(|e| MvpError::ConfigInvalid {
+            message: format!("{}: {e}", path.display()),
+        })?;
+        context.extend(Context::from_serialize(values)?);
+    }
+    Ok(context)
+}
+
+/// Renders `name`; errors quote the loaded source, not the embedded one.
+pub fn render(loaded: &Loaded, name: &str, context: &Context) -> Result<String, MvpError> {
+    super::render(&loaded.tera, name, context)
+        .map_err(|e| MvpError::template_render(name, e, |name| loaded.source(name)))
+}
+
+/// A problem `mvp templates lint` found in one template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub template: String,
+    pub message: String,
+}
+
+/// Variables `name` uses that its context does not provide, and blocks it
+/// defines that no parent template renders.
+pub fn lint(tera: &Tera, name: &str, overrides: Option<&Path>) -> Result<Vec<Finding>, MvpError> {
+    let template = tera.get_template(name)?;
+    let provided = context(name, overrides)?.into_json();
+    let finding = |message: String| Finding {
+        template: name.to_owned(),
+        message,
+    };
+
+    let mut findings: Vec<Finding> = rendered_variables(tera, name)
+        .into_iter()
+        .filter(|path| !is_defined(&provided, path))
+        .map(|path| finding(format!("undefined variable `{path}`")))
+        .collect();
+
+    // 子模板里父模板没有的 block 永远不会被渲染
+    if let Some(parent) = &template.parent {
+        let mut blocks: Vec<&String> = template.blocks.keys().collect();
+        blocks.sort();
+        for block in blocks {
+            let rendered = template.parents.iter().any(|p| {
+                tera.get_template(p)
+                    .is_ok_and(|p| p.blocks.contains_key(block))
+            });
+            if !rendered {
+                findings.push(finding(format!(
+                    "block `{block}` is not defined in `{parent}` and is never rendered"
+                )));
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Whether the dotted `path` resolves in `value`; anything below an array is
+/// assumed to exist.
+fn is_defined(value: &Value, path: &str) -> bool {
+    let mut current = value;
+    for key in path.split('.') {
+        match current {
+            Value::Array(_) => return true,
+            Value::Object(map) => match map.get(key) {
+                Some(next) => current = next,
+                None => return false,
+            },
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Renders every template of `dir` into `out`, then again whenever a file
+/// under `dir` changes. Runs until interrupted.
+pub fn watch(dir: &Path, out: &Path, overrides: Option<&Path>) -> Result<(), MvpError> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    watcher
+        .watch(dir, RecursiveMode::Recursive)
+        .map_err(watch_error)?;
+    if let Some(path) = overrides {
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+    }
+    report::progress(format!(
+        "Watching {} (previews in {})",
+        dir.display(),
+        out.display()
+    ));
+    preview(dir, out, overrides);
+    while let Ok(first) = rx.recv() {
+        // 编辑器保存一次往往触发多个事件，攒一小会儿再渲染；读文件本身的
+        // access 事件不算改动
+        let mut changed = false;
+        let batch = std::iter::once(first).chain(std::iter::from_fn(|| {
+            rx.recv_timeout(Duration::from_millis(100)).ok()
+        }));
+        for event in batch {
+            match event {
+                Ok(event) => changed |= !event.kind.is_access(),
+                Err(e) => report::error(&watch_error(e)),
+            }
+        }
+        if changed {
+            preview(dir, out, overrides);
+        }
+    }
+    Ok(())
+}
+
+/// One preview pass; errors are reported and watching goes on.
+fn preview(dir: &Path, out: &Path, overrides: Option<&Path>) {
+    let result = load(Some(dir)).and_then(|loaded| {
+        let mut plan = Plan::new(out);
+        for name in &loaded.names {
+            let context = context(name, overrides)?;
+            match render(&loaded, name, &context) {
+                Ok(text) => plan.write(name.trim_end_matches(".tera"), text),
+                Err(e) => report::error(&e),
+            }
+        }
+        plan.apply(&ApplyOptions {
+            dry_run: false,
+            on_conflict: Conflict::Overwrite,
+        })
+    });
+    if let Err(e) = result {
+        report::error(&e);
+    }
+}
+
+fn watch_error(e: notify::Error) -> MvpError {
+    MvpError::Custom(format!("Failed to watch templates: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn lint_flags_undefined_variables_and_dead_blocks() {
+        let root = tempdir().unwrap();
+        let dir = root.path().join("templates");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("base.txt"), "{% block body %}{% endblock body %}").unwrap();
+        fs::write(
+            dir.join("page.txt"),
+            "{% extends \"base.txt\" %}{% block body %}{{ author }} {{ missing.field }}{% \
+             endblock body %}{% block footer %}x{% endblock footer %}",
+        )
+        .unwrap();
+        let ctx = root.path().join("ctx.toml");
+        fs::write(&ctx, "missing = { field = 1 }\n").unwrap();
+
+        let loaded = load(Some(&dir)).unwrap();
+        assert_eq!(loaded.names, ["base.txt", "page.txt"]);
+        let messages: Vec<_> = lint(&loaded.tera, "page.txt", None)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "undefined variable `missing.field`",
+                "block `footer` is not defined in `base.txt` and is never rendered",
+            ]
+        );
+
+        assert_eq!(lint(&loaded.tera, "page.txt", Some(&ctx)).unwrap().len(), 1);
+        let context = context("page.txt", Some(&ctx)).unwrap();
+        assert_eq!(render(&loaded, "page.txt", &context).unwrap(), "Levio-Z 1");
+    }
+
+    #[test]
+    fn user_templates_share_partials_and_macros() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("_partials")).unwrap();
+        fs::write(
+            dir.path().join("_partials/sign.txt"),
+            "-- {{ author }}{{ signature }}",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("page.txt"),
+            "{% import \"_partials/license.md\" as license %}{{ license::links(or=\"or\") }}\n{% \
+             include \"_partials/sign.txt\" %}",
+        )
+        .unwrap();
+
+        let loaded = load(Some(dir.path())).unwrap();
+        assert_eq!(loaded.names, ["page.txt"]);
+        let messages: Vec<_> = lint(&loaded.tera, "page.txt", None)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.message)
+            .collect();
+        assert_eq!(messages, ["undefined variable `signature`"]);
+
+        let mut context = context("page.txt", None).unwrap();
+        context.insert("signature", "!");
+        let text = render(&loaded, "page.txt", &context).unwrap();
+        assert!(text.contains("[LICENSE-MIT](LICENSE-MIT) or"), "{text}");
+        assert!(text.ends_with("\n-- Levio-Z!"), "{text}");
+    }
+
+    #[test]
+    fn embedded_templates_lint_clean() {
+        let loaded = load(None).unwrap();
+        for name in &loaded.names {
+            assert_eq!(lint(&loaded.tera, name, None).unwrap(), [], "{name}");
+        }
+    }
+
+    #[test]
+    fn errors_point_into_directory_templates() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("new.txt"), "one\ntwo {{ nme }}\n").unwrap();
+        // 覆盖内置模板时要引用目录里的文件，而不是内置的那份
+        fs::write(
+            dir.path().join("README.md"),
+            "# Readme\n\n\nBy {{ owner }}\n",
+        )
+        .unwrap();
+        let loaded = load(Some(dir.path())).unwrap();
+
+        for (name, expected) in [
+            ("new.txt", "--> new.txt:2:8\n  |\n2 | two {{ nme }}\n"),
+            ("README.md", "--> README.md:4:7\n  |\n4 | By {{ owner }}\n"),
+        ] {
+            let err = render(&loaded, name, &Context::new()).unwrap_err();
+            let report = err.report();
+            assert!(report.contains(expected), "{report}");
+        }
+
+        fs::write(dir.path().join("broken.txt"), "{{ oops").unwrap();
+        let Err(err) = load(Some(dir.path())) else {
+            panic!("loaded a broken template");
+        };
+        assert!(err.report().contains("broken.txt"), "{}", err.report());
+    }
+}
@@ -0,0 +1,465 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use tera::{Context, Tera};
+
+use crate::{
+    config::{CargoConfig, ClippyConfig, DenyConfig, ToolchainConfig},
+    error::MvpError,
+    project::Project,
+};
+
+/// A typed context for one or more embedded templates.
+///
+/// Strategies build these instead of handing the shared [`Context`] to Tera
+/// directly, so a missing or misspelled variable is a compile error (or a
+/// deserialization error naming the field) rather than an empty render.
+pub trait TemplateContext: Serialize + DeserializeOwned + Default {
+    /// Templates rendered with this context, as named in `TEMPLATE_MAP`.
+    const TEMPLATES: &'static [&'static str];
+
+    /// Pulls the fields this context needs out of the shared run context.
+    fn from_context(context: &Context) -> Result<Self, MvpError> {
+        tera::from_value(context.clone().into_json()).map_err(|e| {
+            MvpError::Custom(format!(
+                "Invalid context for {}: {e}",
+                Self::TEMPLATES.join(", ")
+            ))
+        })
+    }
+
+    fn to_context(&self) -> Result<Context, MvpError> {
+        Ok(Context::from_serialize(self)?)
+    }
+
+    fn render(&self, tera: &Tera, template: &str) -> Result<String, MvpError> {
+        debug_assert!(
+            Self::TEMPLATES.contains(&template),
+            "{template} is not rendered with this context"
+        );
+        super::render(tera, template, &self.to_context()?).map_err(|e| {
+            MvpError::template_render(template, e, |name| super::source_in(tera, name))
+        })
+    }
+}
+
+/// `.gitignore`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct GitIgnoreContext {}
+
+impl TemplateContext for GitIgnoreContext {
+    const TEMPLATES: &'static [&'static str] = &[".gitignore"];
+}
+
+/// `LICENSE-APACHE`, `LICENSE-MIT` and the README license section.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct LicenseContext {
+    pub year: i32,
+    pub author: String,
+}
+
+impl TemplateContext for LicenseContext {
+    const TEMPLATES: &'static [&'static str] = &[
+        "LICENSE-APACHE",
+        "LICENSE-MIT",
+        "LICENSE.md",
+        "zh/LICENSE.md",
+    ];
+}
+
+/// `README.md`, filled from the target's `Cargo.toml`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ReadmeContext {
+    pub name: String,
+    pub description: String,
+    pub repository: String,
+    /// SPDX license expression.
+    pub license: String,
+    /// `rust-version`.
+    pub msrv: String,
+    pub features: Vec<ReadmeFeature>,
+    /// `--help` output of the built binary, if any.
+    pub usage: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ReadmeFeature {
+    pub name: String,
+    /// Enabled by the `default` feature.
+    pub default: bool,
+    pub description: String,
+}
+
+impl TemplateContext for ReadmeContext {
+    const TEMPLATES: &'static [&'static str] = &["README.md", "zh/README.md"];
+}
+
+/// `rustfmt.toml`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct FmtContext {}
+
+impl TemplateContext for FmtContext {
+    const TEMPLATES: &'static [&'static str] = &["rustfmt.toml"];
+}
+
+/// `.vscode/settings.json` and `.vscode/tasks.json`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct VscodeContext {
+    /// Library and binary targets get their own tasks.
+    #[serde(default)]
+    pub project: Project,
+}
+
+impl TemplateContext for VscodeContext {
+    const TEMPLATES: &'static [&'static str] = &["vscode/settings.json", "vscode/tasks.json"];
+}
+
+/// `.devcontainer/devcontainer.json`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DevcontainerContext {
+    /// Container name shown by VS Code.
+    pub name: String,
+    /// `.vscode/settings.json` as `mvp add vscode` writes it, comments
+    /// dropped.
+    pub settings: Map<String, Value>,
+}
+
+impl TemplateContext for DevcontainerContext {
+    const TEMPLATES: &'static [&'static str] = &["devcontainer/devcontainer.json"];
+}
+
+/// `Dockerfile` and `.dockerignore` from `mvp add docker`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DockerContext {
+    /// Package name, for `cargo build -p` inside a workspace.
+    pub name: String,
+    /// Directory of the package below the workspace root; empty when the
+    /// package directory is the build context.
+    pub path: String,
+    /// Binary targets copied into the runtime image.
+    pub bins: Vec<String>,
+    /// The binary the image runs.
+    pub entrypoint: String,
+    pub runtime: DockerRuntime,
+    /// Whether there is a `Cargo.lock` to build `--locked` against.
+    pub locked: bool,
+    /// `.dockerignore` patterns, mostly translated from `.gitignore`.
+    pub ignore: Vec<String>,
+}
+
+/// Base of the final image.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DockerRuntime {
+    /// `gcr.io/distroless/cc`: glibc, CA certificates, nothing else
+    #[default]
+    Distroless,
+    /// An empty image with a static musl binary
+    Scratch,
+}
+
+impl TemplateContext for DockerContext {
+    const TEMPLATES: &'static [&'static str] = &["docker/Dockerfile", "docker/.dockerignore"];
+}
+
+/// `rust-toolchain.toml`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ToolchainContext {
+    #[serde(default)]
+    pub toolchain: ToolchainConfig,
+}
+
+impl TemplateContext for ToolchainContext {
+    const TEMPLATES: &'static [&'static str] = &["rust-toolchain.toml"];
+}
+
+/// `clippy.toml`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ClippyContext {
+    #[serde(default)]
+    pub clippy: ClippyConfig,
+}
+
+impl TemplateContext for ClippyContext {
+    const TEMPLATES: &'static [&'static str] = &["clippy.toml"];
+}
+
+/// `deny.toml`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DenyContext {
+    #[serde(default)]
+    pub deny: DenyConfig,
+}
+
+impl TemplateContext for DenyContext {
+    const TEMPLATES: &'static [&'static str] = &["deny.toml"];
+}
+
+/// `.cargo/config.toml`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct CargoContext {
+    #[serde(default)]
+    pub cargo: CargoConfig,
+}
+
+impl TemplateContext for CargoContext {
+    const TEMPLATES: &'static [&'static str] = &["cargo/config.toml"];
+}
+
+/// Every file under `templates/archetypes/`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ArchetypeContext {
+    /// Package name as given to `mvp init`.
+    pub name: String,
+    /// `name` as a Rust identifier, for `use` paths.
+    pub crate_name: String,
+}
+
+impl ArchetypeContext {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            crate_name: name.replace('-', "_"),
+        }
+    }
+}
+
+impl TemplateContext for ArchetypeContext {
+    const TEMPLATES: &'static [&'static str] = &[
+        "archetypes/cli/Cargo.toml.tera",
+        "archetypes/cli/src/cli.rs",
+        "archetypes/cli/src/main.rs",
+        "archetypes/lib/Cargo.toml.tera",
+        "archetypes/lib/src/error.rs",
+        "archetypes/lib/src/lib.rs",
+        "archetypes/lib/tests/integration.rs",
+        "archetypes/proc-macro/Cargo.toml.tera",
+        "archetypes/proc-macro/src/lib.rs",
+        "archetypes/proc-macro/tests/derive.rs",
+        "archetypes/service/Cargo.toml.tera",
+        "archetypes/service/src/main.rs",
+        "archetypes/service/src/routes.rs",
+        "archetypes/workspace/Cargo.toml.tera",
+        "archetypes/workspace/crates/cli/Cargo.toml.tera",
+        "archetypes/workspace/crates/cli/src/main.rs",
+        "archetypes/workspace/crates/core/Cargo.toml.tera",
+        "archetypes/workspace/crates/core/src/lib.rs",
+    ];
+}
+
+/// A new module file from `mvp add module`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ModuleContext {
+    pub name: String,
+    /// Whether to add a `#[cfg(test)] mod tests` block.
+    pub tests: bool,
+}
+
+impl TemplateContext for ModuleContext {
+    const TEMPLATES: &'static [&'static str] = &["module.rs"];
+}
+
+/// `error.rs` from `mvp add error`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ErrorContext {
+    /// One `#[from]` variant per error type the crate already depends on.
+    pub sources: Vec<ErrorSource>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ErrorSource {
+    pub variant: String,
+    /// Path of the wrapped error type, e.g. `serde_json::Error`.
+    pub ty: String,
+    pub message: String,
+}
+
+impl TemplateContext for ErrorContext {
+    const TEMPLATES: &'static [&'static str] = &["error.rs"];
+}
+
+/// `benches/<name>.rs` from `mvp add bench`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct BenchContext {
+    /// Bench target and benchmark group name.
+    pub name: String,
+    /// `name` as a Rust identifier.
+    pub fn_name: String,
+    pub inputs: Vec<u64>,
+}
+
+impl TemplateContext for BenchContext {
+    const TEMPLATES: &'static [&'static str] = &["bench.rs"];
+}
+
+/// Integration test layout from `mvp add tests`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct TestsContext {
+    /// Test target name, i.e. `tests/<name>.rs`.
+    pub name: String,
+    pub crate_name: String,
+    /// Public functions the sample test touches.
+    pub functions: Vec<TestFunction>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct TestFunction {
+    /// Path below the crate, e.g. `basic_01::add_two`.
+    pub path: String,
+    pub test_name: String,
+    /// Call arguments, e.g. `Default::default(), Default::default()`.
+    pub args: String,
+    /// Whether the function returns a value.
+    pub returns: bool,
+    /// Whether that value can be compared with `assert_eq!`.
+    pub comparable: bool,
+}
+
+impl TemplateContext for TestsContext {
+    const TEMPLATES: &'static [&'static str] = &[
+        "tests/common/mod.rs",
+        "tests/integration.rs",
+        "vscode/test-task.json",
+    ];
+}
+
+/// `CHANGELOG.md`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ChangelogContext {}
+
+impl TemplateContext for ChangelogContext {
+    const TEMPLATES: &'static [&'static str] = &["CHANGELOG.md", "zh/CHANGELOG.md"];
+}
+
+/// A release section inserted into `CHANGELOG.md` by `mvp release`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ReleaseContext {
+    pub version: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub sections: Vec<ReleaseSection>,
+}
+
+/// `### Added`, `### Fixed`, ... with one entry per commit.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ReleaseSection {
+    pub title: String,
+    pub entries: Vec<String>,
+}
+
+impl TemplateContext for ReleaseContext {
+    const TEMPLATES: &'static [&'static str] = &["CHANGELOG-release.md"];
+}
+
+/// One SPDX header from `mvp add headers`, without comment markers.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct HeaderContext {
+    pub license: String,
+    /// `2024` or `2021-2024`.
+    pub years: String,
+    pub holder: String,
+}
+
+impl TemplateContext for HeaderContext {
+    const TEMPLATES: &'static [&'static str] = &["header.txt"];
+}
+
+macro_rules! template_contexts {
+    ($($context:ty),* $(,)?) => {
+        /// Every typed context, paired with the templates it renders.
+        pub static CONTEXT_TEMPLATES: &[&[&str]] = &[$(<$context>::TEMPLATES),*];
+
+        /// Builds the default typed context for `template`, or `None` when no
+        /// context type declares it.
+        pub fn default_context(template: &str) -> Option<Result<Context, MvpError>> {
+            $(
+                if <$context>::TEMPLATES.contains(&template) {
+                    return Some(<$context>::default().to_context());
+                }
+            )*
+            None
+        }
+
+        /// Builds the typed context for `template` from the shared run
+        /// context, or `None` when no context type declares it.
+        pub fn context_for(
+            template: &str,
+            context: &Context,
+        ) -> Option<Result<Context, MvpError>> {
+            $(
+                if <$context>::TEMPLATES.contains(&template) {
+                    return Some(<$context>::from_context(context).and_then(|c| c.to_context()));
+                }
+            )*
+            None
+        }
+    };
+}
+
+template_contexts!(
+    GitIgnoreContext,
+    LicenseContext,
+    ReadmeContext,
+    FmtContext,
+    VscodeContext,
+    DevcontainerContext,
+    DockerContext,
+    ToolchainContext,
+    ClippyContext,
+    DenyContext,
+    CargoContext,
+    ArchetypeContext,
+    ModuleContext,
+    ErrorContext,
+    BenchContext,
+    TestsContext,
+    ChangelogContext,
+    ReleaseContext,
+    HeaderContext,
+);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::templates::{PARTIAL_MAP, TEMPLATE_MAP, template_variables};
+
+    fn embedded_tera() -> Tera {
+        let mut tera = Tera::default();
+        tera.add_raw_templates(TEMPLATE_MAP.iter().chain(PARTIAL_MAP).copied())
+            .unwrap();
+        tera
+    }
+
+    #[test]
+    fn every_template_has_a_typed_context() {
+        let declared: BTreeSet<_> = CONTEXT_TEMPLATES
+            .iter()
+            .copied()
+            .flatten()
+            .copied()
+            .collect();
+        let embedded: BTreeSet<_> = TEMPLATE_MAP.iter().map(|(name, _)| *name).collect();
+        assert_eq!(declared, embedded);
+    }
+
+    // 严格模式：模板引用的每个变量都必须出现在它的类型化上下文里
+    #[test]
+    fn render_every_template_in_strict_mode() {
+        let tera = embedded_tera();
+        for (name, _) in TEMPLATE_MAP {
+            let context = default_context(name)
+                .unwrap_or_else(|| panic!("{name} has no typed context"))
+                .unwrap();
+            let json = context.clone().into_json();
+            for variable in template_variables(name).unwrap() {
+                assert!(
+                    tera::dotted_pointer(&json, variable).is_some(),
+                    "{name} uses `{variable}` which its typed context does not define"
+                );
+            }
+            crate::templates::render(&tera, name, &context)
+                .unwrap_or_else(|e| panic!("{name} failed to render: {e:?}"));
+        }
+    }
+}
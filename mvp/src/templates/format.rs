@@ -0,0 +1,218 @@
+//! Data formats `mvp` generates, recognized by file extension: escaping for
+//! values rendered into them and validation of the rendered output.
+//!
+//! Escaping assumes `{{ value }}` sits inside a double-quoted string, which
+//! is how the embedded templates use it; end an expression with `| safe`
+//! when it already produces valid syntax, e.g.
+//! `targets = {{ targets | json_encode() | safe }}`.
+
+use std::{fmt, path::Path};
+
+/// A template or output suffix mvp escapes and validates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+/// Suffixes Tera autoescapes, including the `.tera` manifests of archetypes.
+pub const AUTOESCAPE_SUFFIXES: &[&str] = &[
+    ".html",
+    ".sql",
+    ".json",
+    ".toml",
+    ".yaml",
+    ".yml",
+    ".json.tera",
+    ".toml.tera",
+    ".yaml.tera",
+    ".yml.tera",
+];
+
+impl Format {
+    /// The format of a template or file name, ignoring a `.tera` suffix.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_suffix(".tera").unwrap_or(name);
+        match Path::new(name).extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(&path.file_name()?.to_string_lossy())
+    }
+
+    /// Escapes `text` for the inside of a double-quoted string. The three
+    /// formats share `\"`, `\\`, `\n`, `\t`, ... and `\uXXXX`; they differ in
+    /// which other characters must not appear raw.
+    pub fn escape(self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if self.must_escape(c) => out.push_str(&format!("\\u{:04X}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    fn must_escape(self, c: char) -> bool {
+        match self {
+            Format::Json => c < ' ',
+            Format::Toml => c < ' ' || c == '\u{7f}',
+            // YAML 只允许可打印字符，C1 控制字符和 BOM 也要转义
+            Format::Yaml => c < ' ' || ('\u{7f}'..='\u{9f}').contains(&c) || c == '\u{feff}',
+        }
+    }
+
+    /// Parses `text` as this format. JSON may carry `//` and `/* */`
+    /// comments, as VS Code settings do.
+    pub fn validate(self, text: &str) -> Result<(), String> {
+        match self {
+            Format::Json => parse_jsonc(text).map(drop).map_err(|e| e.to_string()),
+            Format::Toml => toml::from_str::<toml::Table>(text)
+                .map(drop)
+                .map_err(|e| e.to_string().trim_end().to_owned()),
+            Format::Yaml => serde_yaml_ng::from_str::<serde_yaml_ng::Value>(text)
+                .map(drop)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Json => "JSON",
+            Format::Toml => "TOML",
+            Format::Yaml => "YAML",
+        })
+    }
+}
+
+/// Parses JSON that may carry comments, such as VS Code settings.
+pub fn parse_jsonc(text: &str) -> serde_json::Result<serde_json::Value> {
+    serde_json::from_str(&strip_json_comments(text))
+}
+
+/// Blanks out comments outside strings, keeping line and column numbers of
+/// the rest intact for error messages.
+fn strip_json_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.next_if(|&c| c != '\n').is_some() {
+                    out.push(' ');
+                }
+                out.push(' ');
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                out.push_str("  ");
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    out.push(if c == '\n' { '\n' } else { ' ' });
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quotes, backslashes and control characters every format must escape.
+    const AWKWARD: &str = "O'Brien \"Bob\" \\ tab\there\nnew\u{7f}line\u{85}";
+
+    #[test]
+    fn json_values_round_trip() {
+        let text = format!(
+            "{{ \"author\": \"{}\" }} // note",
+            Format::Json.escape(AWKWARD)
+        );
+        Format::Json.validate(&text).unwrap();
+        assert_eq!(parse_jsonc(&text).unwrap()["author"], AWKWARD);
+        assert_eq!(
+            strip_json_comments("{\"a\": \"//x\" /* c\n */}"),
+            "{\"a\": \"//x\"     \n   }"
+        );
+    }
+
+    #[test]
+    fn toml_values_round_trip() {
+        let text = format!("author = \"{}\"\n", Format::Toml.escape(AWKWARD));
+        Format::Toml.validate(&text).unwrap();
+        let table: toml::Table = toml::from_str(&text).unwrap();
+        assert_eq!(table["author"].as_str(), Some(AWKWARD));
+    }
+
+    #[test]
+    fn yaml_values_round_trip() {
+        let text = format!("author: \"{}\"\n", Format::Yaml.escape(AWKWARD));
+        Format::Yaml.validate(&text).unwrap();
+        let value: serde_yaml_ng::Value = serde_yaml_ng::from_str(&text).unwrap();
+        assert_eq!(value["author"].as_str(), Some(AWKWARD));
+    }
+
+    #[test]
+    fn invalid_documents_fail_validation() {
+        assert!(Format::Json.validate("{ \"a\": 1, }").is_err());
+        assert!(Format::Toml.validate("author = \"O\"Brien\"\n").is_err());
+        assert!(Format::Yaml.validate("author: \"O\"Brien\"\n").is_err());
+    }
+
+    #[test]
+    fn templates_escape_for_their_extension() {
+        assert_eq!(
+            Format::from_name("archetypes/cli/Cargo.toml.tera"),
+            Some(Format::Toml)
+        );
+        assert_eq!(Format::from_name("README.md"), None);
+
+        // 同一个值按模板的扩展名转义
+        let mut tera = crate::templates::embedded().unwrap();
+        tera.add_raw_templates([
+            ("author.toml", "author = \"{{ author }}\"\n"),
+            ("author.html", "<p>{{ author }}</p>"),
+        ])
+        .unwrap();
+        let mut context = tera::Context::new();
+        context.insert("author", "Bob \"<b>\"");
+        let toml = crate::templates::render(&tera, "author.toml", &context).unwrap();
+        assert_eq!(toml, "author = \"Bob \\\"<b>\\\"\"\n");
+        let html = crate::templates::render(&tera, "author.html", &context).unwrap();
+        assert_eq!(html, "<p>Bob &quot;&lt;b&gt;&quot;</p>");
+    }
+}
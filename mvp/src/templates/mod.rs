@@ -0,0 +1,120 @@
+use std::{cell::Cell, collections::HashMap, sync::LazyLock};
+
+use tera::{Context, Result as TeraResult, Tera, Value};
+
+use self::format::{AUTOESCAPE_SUFFIXES, Format};
+use crate::i18n::Lang;
+
+// build.rs 在编译期校验 templates/ 下的所有模板，并生成 TEMPLATE_MAP 等常量
+include!(concat!(env!("OUT_DIR"), "/templates.rs"));
+
+pub mod authoring;
+pub mod contexts;
+pub mod format;
+pub mod variables;
+
+// Custom filter: does nothing
+fn do_nothing_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
+    Ok(value.clone())
+}
+
+/// Global template singleton holding every embedded template.
+pub static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| embedded().unwrap());
+
+/// Embedded templates with Chinese translations in place of the defaults.
+static TEMPLATES_ZH: LazyLock<Tera> = LazyLock::new(|| localize(embedded().unwrap(), Lang::Zh));
+
+/// The embedded templates as seen in `lang`: `zh/README.md` is rendered for
+/// `README.md`, and templates without a translation stay as they are.
+pub fn localized(lang: Lang) -> &'static Tera {
+    match lang {
+        Lang::En => &TEMPLATES,
+        Lang::Zh => &TEMPLATES_ZH,
+    }
+}
+
+fn localize(mut tera: Tera, lang: Lang) -> Tera {
+    let Some(dir) = lang.template_dir() else {
+        return tera;
+    };
+    let prefix = format!("{dir}/");
+    let translations = TEMPLATE_MAP
+        .iter()
+        .chain(PARTIAL_MAP)
+        .filter_map(|(name, source)| Some((name.strip_prefix(&prefix)?, *source)));
+    tera.add_raw_templates(translations)
+        .expect("translations are checked by build.rs");
+    tera
+}
+
+/// Builds a fresh [`Tera`] from the embedded templates, configured the way
+/// `mvp` renders them.
+pub fn embedded() -> TeraResult<Tera> {
+    let mut tera = Tera::default();
+
+    // 一次性注册 build.rs 生成的模板，build.rs 已经校验过语法
+    tera.add_raw_templates(TEMPLATE_MAP.iter().chain(PARTIAL_MAP).copied())?;
+
+    // 可选配置
+    tera.autoescape_on(AUTOESCAPE_SUFFIXES.to_vec());
+    tera.set_escape_fn(escape);
+    tera.register_filter("do_nothing", do_nothing_filter);
+    Ok(tera)
+}
+
+thread_local! {
+    /// Format of the template [`render`] is working on, for [`escape`].
+    static RENDERING: Cell<Option<Format>> = const { Cell::new(None) };
+}
+
+/// Renders `name`, escaping values for its format: Tera hands the escape
+/// function only the text, so the format is passed along on the side. Render
+/// through here rather than [`Tera::render`].
+pub fn render(tera: &Tera, name: &str, context: &Context) -> TeraResult<String> {
+    let previous = RENDERING.replace(Format::from_name(name));
+    let result = tera.render(name, context);
+    RENDERING.set(previous);
+    result
+}
+
+fn escape(text: &str) -> String {
+    match RENDERING.get() {
+        Some(format) => format.escape(text),
+        None => tera::escape_html(text),
+    }
+}
+
+/// Source of the embedded template or partial `name`.
+pub fn source(name: &str) -> Option<&'static str> {
+    TEMPLATE_MAP
+        .iter()
+        .chain(PARTIAL_MAP)
+        .find(|(template, _)| *template == name)
+        .map(|(_, source)| *source)
+}
+
+/// Source `tera` renders for `name`: the translation when `tera` is one of
+/// the [`localized`] sets, else the embedded template or partial.
+pub fn source_in(tera: &Tera, name: &str) -> Option<&'static str> {
+    let translated = [Lang::En, Lang::Zh]
+        .into_iter()
+        .find(|&lang| std::ptr::eq(tera, localized(lang)))
+        .and_then(Lang::template_dir)
+        .and_then(|dir| source(&format!("{dir}/{name}")));
+    translated.or_else(|| source(name))
+}
+
+/// Whether `name` lives in a `_partials/` directory: embedded (and loaded
+/// from template directories) so other templates can `include`, `extends` or
+/// `import` it, but never rendered on its own.
+pub fn is_partial(name: &str) -> bool {
+    name.starts_with("_partials/") || name.contains("/_partials/")
+}
+
+/// Looks up the variables `name` needs, as computed by `build.rs`.
+pub fn template_variables(name: &str) -> Option<&'static [&'static str]> {
+    TEMPLATE_VARIABLES
+        .iter()
+        .find(|(template, _)| *template == name)
+        .map(|(_, variables)| *variables)
+}
@@ -0,0 +1,300 @@
+// 这个文件同时被 build.rs 通过 #[path] 引入，所以只能依赖 std 和 tera
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use tera::{
+    Tera,
+    ast::{Block, Expr, ExprVal, FunctionCall, Node},
+};
+
+/// Collects the context variables a parsed template needs.
+///
+/// Variables are reported by their dotted path (`project.name`), without any
+/// `[..]` indexing. Names bound inside the template (`for`, `set`, macro
+/// arguments, `loop`) are skipped, as are values guarded by a `default`
+/// filter or an `is defined` test, since those may legitimately be missing.
+pub fn referenced_variables(ast: &[Node]) -> BTreeSet<String> {
+    let mut walker = Walker::new(None);
+    walker.nodes(ast);
+    walker.found
+}
+
+/// Like [`referenced_variables`] for the template `name` of `tera`, as it
+/// renders: `{% include %}`d templates are walked in place, and a child of
+/// `{% extends %}` is walked through its root parent with each block
+/// replaced by the most derived definition (and `super()` by the next one).
+pub fn rendered_variables(tera: &Tera, name: &str) -> BTreeSet<String> {
+    let Ok(template) = tera.get_template(name) else {
+        return BTreeSet::new();
+    };
+    let ast = match template.parents.last() {
+        Some(root) => match tera.get_template(root) {
+            Ok(root) => &root.ast,
+            Err(_) => return BTreeSet::new(),
+        },
+        None => &template.ast,
+    };
+    let mut walker = Walker::new(Some(tera));
+    walker.blocks = Some(&template.blocks_definitions);
+    walker.including.push(name.to_owned());
+    walker.nodes(ast);
+    walker.found
+}
+
+struct Walker<'a> {
+    scopes: Vec<HashSet<String>>,
+    found: BTreeSet<String>,
+    /// Where included templates are looked up.
+    tera: Option<&'a Tera>,
+    /// Block definitions of the template being rendered, most derived
+    /// first.
+    blocks: Option<&'a HashMap<String, Vec<(String, Block)>>>,
+    /// Blocks being walked with the definition level, for `super()`.
+    block_stack: Vec<(&'a str, usize)>,
+    /// Templates being walked, so an include cycle stops.
+    including: Vec<String>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(tera: Option<&'a Tera>) -> Self {
+        Self {
+            scopes: vec![HashSet::from([
+                "loop".to_owned(),
+                "__tera_context".to_owned(),
+            ])],
+            found: BTreeSet::new(),
+            tera,
+            blocks: None,
+            block_stack: Vec::new(),
+            including: Vec::new(),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &'a [Node]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn scoped(&mut self, bound: impl IntoIterator<Item = String>, body: &'a [Node]) {
+        self.scopes.push(bound.into_iter().collect());
+        self.nodes(body);
+        self.scopes.pop();
+    }
+
+    /// Walks definition `level` of block `name`, if there is one.
+    fn block(&mut self, name: &'a str, level: usize) {
+        let Some((_, block)) = self
+            .blocks
+            .and_then(|blocks| blocks.get(name))
+            .and_then(|definitions| definitions.get(level))
+        else {
+            return;
+        };
+        self.block_stack.push((name, level));
+        self.scoped([], &block.body);
+        self.block_stack.pop();
+    }
+
+    /// Walks the first of `names` that exists, in the current scope, as
+    /// Tera renders it.
+    fn include(&mut self, names: &[String]) {
+        let Some(tera) = self.tera else {
+            return;
+        };
+        let Some(template) = names.iter().find_map(|name| tera.get_template(name).ok()) else {
+            return;
+        };
+        if self.including.contains(&template.name) {
+            return;
+        }
+        // 被 include 的模板没有子模板的 block 覆盖
+        let blocks = self.blocks.take();
+        let block_stack = std::mem::take(&mut self.block_stack);
+        self.including.push(template.name.clone());
+        self.nodes(&template.ast);
+        self.including.pop();
+        self.blocks = blocks;
+        self.block_stack = block_stack;
+    }
+
+    fn node(&mut self, node: &'a Node) {
+        match node {
+            Node::VariableBlock(_, expr) => self.expr(expr),
+            Node::MacroDefinition(_, def, _) => {
+                for default in def.args.values().flatten() {
+                    self.expr(default);
+                }
+                self.scoped(def.args.keys().cloned(), &def.body);
+            }
+            Node::Set(_, set) => {
+                self.expr(&set.value);
+                let scope = if set.global {
+                    self.scopes.first_mut()
+                } else {
+                    self.scopes.last_mut()
+                };
+                scope.expect("root scope").insert(set.key.clone());
+            }
+            Node::FilterSection(_, section, _) => {
+                self.call(&section.filter);
+                self.scoped([], &section.body);
+            }
+            Node::Block(_, block, _) => {
+                if self
+                    .blocks
+                    .is_some_and(|blocks| blocks.contains_key(&block.name))
+                {
+                    self.block(&block.name, 0);
+                } else {
+                    self.scoped([], &block.body);
+                }
+            }
+            Node::Super => {
+                if let Some(&(name, level)) = self.block_stack.last() {
+                    self.block(name, level + 1);
+                }
+            }
+            Node::Include(_, names, _) => self.include(names),
+            Node::Forloop(_, forloop, _) => {
+                self.expr(&forloop.container);
+                let bound = forloop.key.iter().cloned().chain([forloop.value.clone()]);
+                self.scoped(bound, &forloop.body);
+                if let Some(empty) = &forloop.empty_body {
+                    self.scoped([], empty);
+                }
+            }
+            Node::If(cond, _) => {
+                for (_, expr, body) in &cond.conditions {
+                    self.expr(expr);
+                    self.scoped([], body);
+                }
+                if let Some((_, body)) = &cond.otherwise {
+                    self.scoped([], body);
+                }
+            }
+            Node::Text(_)
+            | Node::Extends(..)
+            | Node::ImportMacro(..)
+            | Node::Raw(..)
+            | Node::Break(_)
+            | Node::Continue(_)
+            | Node::Comment(..) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        for filter in &expr.filters {
+            self.call(filter);
+        }
+        if expr.has_default_filter() {
+            return;
+        }
+        self.value(&expr.val);
+    }
+
+    fn call(&mut self, call: &FunctionCall) {
+        for arg in call.args.values() {
+            self.expr(arg);
+        }
+    }
+
+    fn value(&mut self, val: &ExprVal) {
+        match val {
+            ExprVal::Ident(ident) => self.ident(ident),
+            ExprVal::Math(math) => {
+                self.expr(&math.lhs);
+                self.expr(&math.rhs);
+            }
+            ExprVal::Logic(logic) => {
+                self.expr(&logic.lhs);
+                self.expr(&logic.rhs);
+            }
+            ExprVal::Test(test) => {
+                for arg in &test.args {
+                    self.expr(arg);
+                }
+                if !matches!(test.name.as_str(), "defined" | "undefined") {
+                    self.ident(&test.ident);
+                }
+            }
+            ExprVal::MacroCall(call) => {
+                for arg in call.args.values() {
+                    self.expr(arg);
+                }
+            }
+            ExprVal::FunctionCall(call) => self.call(call),
+            ExprVal::Array(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprVal::StringConcat(concat) => {
+                for value in &concat.values {
+                    self.value(value);
+                }
+            }
+            ExprVal::In(within) => {
+                self.expr(&within.lhs);
+                self.expr(&within.rhs);
+            }
+            ExprVal::String(_) | ExprVal::Int(_) | ExprVal::Float(_) | ExprVal::Bool(_) => {}
+        }
+    }
+
+    fn ident(&mut self, ident: &str) {
+        let path = ident.split('[').next().unwrap_or(ident);
+        let root = path.split('.').next().unwrap_or(path);
+        if !self.scopes.iter().any(|scope| scope.contains(root)) {
+            self.found.insert(path.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tera::Tera;
+
+    use super::*;
+
+    fn variables_of(source: &str) -> Vec<String> {
+        let mut tera = Tera::default();
+        tera.add_raw_template("t", source).unwrap();
+        let template = tera.get_template("t").unwrap();
+        referenced_variables(&template.ast).into_iter().collect()
+    }
+
+    #[test]
+    fn collects_dotted_paths_and_skips_bound_names() {
+        let source = "{{ author }} {% for dep in project.deps %}{{ dep.name }}{{ loop.index }}{% \
+                      endfor %}{% set x = year %}{{ x }}";
+        assert_eq!(variables_of(source), ["author", "project.deps", "year"]);
+    }
+
+    #[test]
+    fn follows_includes_and_block_overrides() {
+        let mut tera = Tera::default();
+        tera.add_raw_templates([
+            ("_partials/row", "{{ item.name }} {{ author }}"),
+            (
+                "base",
+                "{{ title }}{% block body %}{{ unused }}{% endblock body %}{% block footer %}{{ \
+                 year }}{% endblock footer %}",
+            ),
+            (
+                "page",
+                "{% extends \"base\" %}{% block body %}{% for item in items %}{% include \
+                 \"_partials/row\" %}{% endfor %}{% endblock body %}{% block footer %}{{ super() \
+                 }}{{ license }}{% endblock footer %}",
+            ),
+        ])
+        .unwrap();
+        let variables: Vec<_> = rendered_variables(&tera, "page").into_iter().collect();
+        assert_eq!(variables, ["author", "items", "license", "title", "year"]);
+    }
+
+    #[test]
+    fn skips_optional_variables() {
+        let source = "{{ name | default(value=\"x\") }}{% if repo is defined %}yes{% endif %}";
+        assert!(variables_of(source).is_empty());
+    }
+}
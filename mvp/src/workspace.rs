@@ -0,0 +1,209 @@
+//! `mvp add --workspace` and `--package <name>`: running strategies in
+//! several members of a Cargo workspace at once.
+//!
+//! Each member gets its own plan, rendered with `project` describing that
+//! member. The member plans are merged into one plan rooted at the workspace
+//! root, so reported paths name the member they belong to, and nothing is
+//! written unless every member planned cleanly.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    GeneratorBuilder,
+    error::MvpError,
+    i18n::Message,
+    plan::{Applied, ApplyOptions, Plan},
+    project::{self, read_manifest},
+    report,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    /// Package name.
+    pub name: String,
+    /// Directory relative to the workspace root; empty for the root package.
+    pub dir: PathBuf,
+}
+
+/// Members of the workspace at `root`: the root package, if there is one,
+/// then the `members` globs minus `exclude`.
+pub fn members(root: &Path) -> Result<Vec<Member>, MvpError> {
+    let mut dirs = vec![PathBuf::new()];
+    for manifest in project::member_manifests(root)? {
+        let dir = manifest.parent().map(Path::to_path_buf).unwrap_or_default();
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    let mut members = Vec::new();
+    for dir in dirs {
+        let manifest = read_manifest(root, &dir.join("Cargo.toml"))?;
+        // 虚拟清单没有 package，不算成员
+        if let Some(name) = manifest.package_name() {
+            members.push(Member {
+                name: name.to_owned(),
+                dir,
+            });
+        }
+    }
+    Ok(members)
+}
+
+/// The members named in `packages`, in workspace order; all of them when
+/// `packages` is empty.
+pub fn select(members: Vec<Member>, packages: &[String]) -> Result<Vec<Member>, MvpError> {
+    if let Some(unknown) = packages
+        .iter()
+        .find(|name| !members.iter().any(|m| &m.name == *name))
+    {
+        let names: Vec<_> = members.iter().map(|m| m.name.as_str()).collect();
+        return Err(MvpError::InvalidArgs {
+            strategy: "--package".into(),
+            message: format!(
+                "no workspace member named `{unknown}` (members: {})",
+                names.join(", ")
+            ),
+        });
+    }
+    Ok(members
+        .into_iter()
+        .filter(|m| packages.is_empty() || packages.contains(&m.name))
+        .collect())
+}
+
+/// The merged plan of a workspace run.
+#[derive(Debug, Clone)]
+pub struct WorkspacePlan {
+    plan: Plan,
+    /// Each member with the range of its operations in `plan`.
+    members: Vec<(Member, Range<usize>)>,
+}
+
+impl WorkspacePlan {
+    pub fn plan(&self) -> &Plan {
+        &self.plan
+    }
+
+    /// Applies the merged plan, then reports how each member fared.
+    pub fn apply(&self, options: &ApplyOptions) -> Result<Vec<Applied>, MvpError> {
+        let applied = self.plan.apply(options)?;
+        for (member, ops) in &self.members {
+            report::member(&member.name, &member.dir, &applied[ops.clone()]);
+        }
+        Ok(applied)
+    }
+}
+
+/// Plans every member of the workspace at `root` with the generator `build`
+/// returns for it; the target is set here. A failing member is reported and
+/// the others are still planned, so one run shows every problem.
+pub fn plan(
+    root: &Path,
+    members: &[Member],
+    build: impl Fn(&Member) -> GeneratorBuilder,
+) -> Result<WorkspacePlan, MvpError> {
+    let mut plan = Plan::new(root);
+    let mut planned = Vec::with_capacity(members.len());
+    let mut failed = Vec::new();
+    for member in members {
+        report::progress(Message::Member(&member.name));
+        match build(member).target(root.join(&member.dir)).plan() {
+            Ok(child) => {
+                let start = plan.ops().len();
+                plan.nest(&member.dir, child);
+                planned.push((member.clone(), start..plan.ops().len()));
+            }
+            Err(e) => {
+                report::error(&e);
+                failed.push(member.name.as_str());
+            }
+        }
+    }
+    if !failed.is_empty() {
+        return Err(MvpError::CheckFailed {
+            message: format!(
+                "{} of {} workspace member(s) failed: {}",
+                failed.len(),
+                members.len(),
+                failed.join(", ")
+            ),
+        });
+    }
+    Ok(WorkspacePlan {
+        plan,
+        members: planned,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{Generator, config::Config};
+
+    #[test]
+    fn runs_strategies_in_selected_members() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let write = |path: &str, text: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write(
+            "Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+             [workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+        );
+        for name in ["core", "cli", "old"] {
+            write(
+                &format!("crates/{name}/Cargo.toml"),
+                &format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n"),
+            );
+        }
+
+        let all = members(root).unwrap();
+        let names: Vec<_> = all.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["app", "cli", "core"]);
+        assert_eq!(all[0].dir, PathBuf::new());
+
+        let err = select(all.clone(), &["old".into()]).unwrap_err();
+        assert!(err.to_string().contains("members: app, cli, core"), "{err}");
+
+        let selected = select(all, &["core".into(), "app".into()]).unwrap();
+        let plan = plan(root, &selected, |_| {
+            Generator::builder()
+                .config(Config::default())
+                .strategies(["fmt"])
+        })
+        .unwrap();
+        let paths: Vec<_> = plan.plan().ops().iter().map(|op| op.path()).collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("rustfmt.toml"),
+                Path::new("crates/core/rustfmt.toml")
+            ]
+        );
+        plan.apply(&ApplyOptions::default()).unwrap();
+        assert!(root.join("crates/core/rustfmt.toml").is_file());
+        assert!(!root.join("crates/cli/rustfmt.toml").exists());
+
+        let err = super::plan(root, &selected, |_| {
+            Generator::builder()
+                .config(Config::default())
+                .strategies(["no-such-strategy"])
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2 of 2 workspace member(s) failed: app, core"
+        );
+    }
+}
@@ -0,0 +1,18 @@
+mod cli;
+
+use clap::Parser;
+
+use crate::cli::{Cli, Commands};
+
+fn main() {
+    let cli = Cli::parse();
+
+    if cli.verbose > 0 {
+        eprintln!("verbosity: {}", cli.verbose);
+    }
+
+    match cli.command {
+        Commands::Hello { name } => println!("Hello, {}!", name.as_deref().unwrap_or("world")),
+        Commands::Version => println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+    }
+}
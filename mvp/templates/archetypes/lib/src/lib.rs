@@ -0,0 +1,24 @@
+//! {{ name }}
+
+pub mod error;
+
+pub use error::{Error, Result};
+
+/// Parses a non-empty, trimmed name.
+pub fn parse_name(input: &str) -> Result<&str> {
+    let name = input.trim();
+    if name.is_empty() {
+        return Err(Error::Empty);
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_names() {
+        assert_eq!(parse_name("  ferris ").unwrap(), "ferris");
+    }
+}
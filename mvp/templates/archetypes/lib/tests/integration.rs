@@ -0,0 +1,6 @@
+use {{ crate_name }}::{Error, parse_name};
+
+#[test]
+fn rejects_empty_input() {
+    assert!(matches!(parse_name("   "), Err(Error::Empty)));
+}
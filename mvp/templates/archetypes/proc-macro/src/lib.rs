@@ -0,0 +1,21 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, parse_macro_input};
+
+/// Implements `fn type_name() -> &'static str` for the annotated type.
+#[proc_macro_derive(TypeName)]
+pub fn derive_type_name(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            pub fn type_name() -> &'static str {
+                #name
+            }
+        }
+    }
+    .into()
+}
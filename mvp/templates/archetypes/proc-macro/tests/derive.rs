@@ -0,0 +1,9 @@
+use {{ crate_name }}::TypeName;
+
+#[derive(TypeName)]
+struct Ferris;
+
+#[test]
+fn derives_type_name() {
+    assert_eq!(Ferris::type_name(), "Ferris");
+}
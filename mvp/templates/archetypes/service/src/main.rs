@@ -0,0 +1,23 @@
+mod routes;
+
+use std::net::SocketAddr;
+
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let addr: SocketAddr = std::env::var("{{ crate_name | upper }}_ADDR")
+        .ok()
+        .and_then(|addr| addr.parse().ok())
+        .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 3000)));
+
+    let listener = TcpListener::bind(addr).await?;
+    println!("listening on http://{addr}");
+    axum::serve(listener, routes::router())
+        .with_graceful_shutdown(shutdown())
+        .await
+}
+
+async fn shutdown() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,15 @@
+use axum::{Router, routing::get};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/health", get(health))
+}
+
+async fn index() -> &'static str {
+    "{{ name }}"
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
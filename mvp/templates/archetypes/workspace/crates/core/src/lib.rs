@@ -0,0 +1,14 @@
+/// Shared logic for the `{{ name }}` workspace.
+pub fn greeting(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greets() {
+        assert_eq!(greeting("ferris"), "Hello, ferris!");
+    }
+}
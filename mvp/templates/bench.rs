@@ -0,0 +1,21 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+// 待测函数，换成 crate 里要比较的实现
+fn {{ fn_name }}(n: u64) -> u64 {
+    (0..n).sum()
+}
+
+fn bench_{{ fn_name }}(c: &mut Criterion) {
+    let mut group = c.benchmark_group("{{ name }}");
+    for input in [{{ inputs | join(sep=", ") }}] {
+        group.bench_with_input(BenchmarkId::from_parameter(input), &input, |b, &input| {
+            b.iter(|| {{ fn_name }}(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_{{ fn_name }});
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+{%- for source in sources %}
+    #[error("{{ source.message }}: {0}")]
+    {{ source.variant }}(#[from] {{ source.ty }}),
+{%- endfor %}
+    #[error("{0}")]
+    Custom(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
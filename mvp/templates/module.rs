@@ -0,0 +1,7 @@
+{%- if tests -%}
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn {{ name }}_works() {}
+}
+{% endif -%}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::{CwdGuard, TempDir};
+{% for function in functions %}
+#[test]
+fn {{ function.test_name }}() {
+{%- if function.returns %}
+    let _result = {{ crate_name }}::{{ function.path }}({{ function.args }});
+{%- if function.comparable %}
+    // Fill in what `{{ function.path }}` should return, then drop the `_` above:
+    // assert_eq!(result, <expected>);
+{%- else %}
+    // Check the result, then drop the `_` above:
+    // assert!(<condition on result>);
+{%- endif %}
+{%- else %}
+    {{ crate_name }}::{{ function.path }}({{ function.args }});
+{%- endif %}
+}
+{% endfor %}
+/// Tests that touch the filesystem get their own directory; `CwdGuard` runs
+/// them one at a time, so only take it when relative paths matter.
+#[test]
+fn runs_in_a_temp_dir() {
+    let dir = TempDir::new();
+    let _cwd = CwdGuard::enter(dir.path());
+    assert!(std::env::current_dir().unwrap().ends_with(dir.path().file_name().unwrap()));
+}